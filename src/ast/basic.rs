@@ -96,23 +96,9 @@ impl<E> BoundedSeparated<E> {
         self
     }
 
-    pub fn map<E2>(self, f: impl Fn(E) -> E2) -> BoundedSeparated<E2> {
-        let elems = self
-            .elems
-            .into_iter()
-            .map(|(s0, e, s1)| (s0, f(e), s1))
-            .collect::<Vec<_>>();
-
-        BoundedSeparated {
-            elems,
-            trailing: self.trailing,
-            span: self.span,
-        }
-    }
-
     pub fn remove_map<E1, E2>(
         self,
-        f: impl Fn(E) -> Result<E1, E2>,
+        mut f: impl FnMut(E) -> Result<E1, E2>,
     ) -> (BoundedSeparated<E1>, Vec<(Space, E2, Space)>) {
         let mut kept = vec![];
         let mut removed = vec![];
@@ -142,3 +128,50 @@ impl BoundedSeparated<TableConstrElem> {
         TableConstr(self)
     }
 }
+
+/// Structural equality that ignores everything a pretty printer may change.
+///
+/// Besides [`Span`]s, this ignores whitespace and comments, trailing separators
+/// and parentheses, since those are either layout or only make the structure
+/// explicit. A program and the program parsed from its pretty-printed output
+/// should always be equal in this sense.
+pub trait SpanlessEq {
+    fn spanless_eq(&self, other: &Self) -> bool;
+}
+
+impl<T: SpanlessEq> SpanlessEq for Box<T> {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        (**self).spanless_eq(other)
+    }
+}
+
+impl<T: SpanlessEq> SpanlessEq for Option<T> {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Some(a), Some(b)) => a.spanless_eq(b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl SpanlessEq for Space {
+    fn spanless_eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl SpanlessEq for Ident {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl<E: SpanlessEq> SpanlessEq for BoundedSeparated<E> {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        self.elems.len() == other.elems.len()
+            && (self.elems.iter())
+                .zip(&other.elems)
+                .all(|((_, a, _), (_, b, _))| a.spanless_eq(b))
+    }
+}
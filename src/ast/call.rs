@@ -1,6 +1,6 @@
 use crate::span::{HasSpan, Span};
 
-use super::{Expr, Space, TableConstr};
+use super::{Expr, Space, SpanlessEq, TableConstr};
 
 #[derive(Debug, Clone)]
 pub enum Call {
@@ -81,3 +81,28 @@ impl Call {
         Expr::Call(self)
     }
 }
+
+impl SpanlessEq for Call {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                Self::Arg { expr, arg, .. },
+                Self::Arg {
+                    expr: expr2,
+                    arg: arg2,
+                    ..
+                },
+            ) => expr.spanless_eq(expr2) && arg.spanless_eq(arg2),
+            (Self::NoArg { expr, .. }, Self::NoArg { expr: expr2, .. }) => expr.spanless_eq(expr2),
+            (
+                Self::Constr { expr, constr, .. },
+                Self::Constr {
+                    expr: expr2,
+                    constr: constr2,
+                    ..
+                },
+            ) => expr.spanless_eq(expr2) && constr.spanless_eq(constr2),
+            _ => false,
+        }
+    }
+}
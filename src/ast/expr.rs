@@ -2,7 +2,7 @@ use std::fmt;
 
 use crate::span::{HasSpan, Span};
 
-use super::{Call, Field, FuncDef, Lit, Space, TableConstr, TableDestr, Var};
+use super::{Call, Field, FuncDef, Lit, Space, SpanlessEq, TableConstr, TableDestr, Var};
 
 // Warning: If you change these precedences and associativities, you need to
 // update the parser and pretty-printer as well.
@@ -16,7 +16,7 @@ pub enum Assoc {
     Right,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BinOp {
     /// `*`
     Mul,
@@ -47,6 +47,24 @@ pub enum BinOp {
 }
 
 impl BinOp {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::Mul => "*",
+            Self::Div => "/",
+            Self::Mod => "%",
+            Self::Add => "+",
+            Self::Sub => "-",
+            Self::Eq => "==",
+            Self::Neq => "!=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::And => "and",
+            Self::Or => "or",
+        }
+    }
+
     /// The higher the precedence, the more strongly the operator binds.
     pub fn precedence(self) -> u8 {
         match self {
@@ -91,7 +109,7 @@ pub enum Expr {
     /// Structure: `( s0 inner s1 )`
     Paren {
         s0: Space,
-        inner: Box<Expr>,
+        inner: Box<Self>,
         s1: Space,
         span: Span,
     },
@@ -102,7 +120,7 @@ pub enum Expr {
     Neg {
         minus: Span,
         s0: Space,
-        expr: Box<Expr>,
+        expr: Box<Self>,
         span: Span,
     },
 
@@ -112,7 +130,7 @@ pub enum Expr {
     Not {
         not: Span,
         s0: Space,
-        expr: Box<Expr>,
+        expr: Box<Self>,
         span: Span,
     },
 
@@ -122,13 +140,19 @@ pub enum Expr {
     ///
     /// Structure: `left s0 op s1 right`
     BinOp {
-        left: Box<Expr>,
+        left: Box<Self>,
         s0: Space,
         op: BinOp,
         s1: Space,
-        right: Box<Expr>,
+        right: Box<Self>,
         span: Span,
     },
+
+    /// Code that could not be parsed.
+    ///
+    /// The parser inserts these while recovering from errors, so a program
+    /// containing one was never parsed successfully.
+    Error(Span),
 }
 
 impl fmt::Debug for Expr {
@@ -221,6 +245,7 @@ impl fmt::Debug for Expr {
                 .field("right", right)
                 .field("span", span)
                 .finish(),
+            Self::Error(span) => f.debug_tuple("Expr::Error").field(span).finish(),
         }
     }
 }
@@ -239,6 +264,7 @@ impl HasSpan for Expr {
             Self::Neg { span, .. } => *span,
             Self::Not { span, .. } => *span,
             Self::BinOp { span, .. } => *span,
+            Self::Error(span) => *span,
         }
     }
 }
@@ -248,3 +274,42 @@ impl Expr {
         Box::new(self)
     }
 }
+
+impl Expr {
+    /// The expression inside any parentheses.
+    fn unparen(&self) -> &Self {
+        match self {
+            Self::Paren { inner, .. } => inner.unparen(),
+            _ => self,
+        }
+    }
+}
+
+impl SpanlessEq for Expr {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        match (self.unparen(), other.unparen()) {
+            (Self::Lit(lit), Self::Lit(lit2)) => lit.spanless_eq(lit2),
+            (Self::Call(call), Self::Call(call2)) => call.spanless_eq(call2),
+            (Self::Field(field), Self::Field(field2)) => field.spanless_eq(field2),
+            (Self::Var(var), Self::Var(var2)) => var.spanless_eq(var2),
+            (Self::TableConstr(constr), Self::TableConstr(constr2)) => constr.spanless_eq(constr2),
+            (Self::TableDestr(destr), Self::TableDestr(destr2)) => destr.spanless_eq(destr2),
+            (Self::FuncDef(def), Self::FuncDef(def2)) => def.spanless_eq(def2),
+            (Self::Neg { expr, .. }, Self::Neg { expr: expr2, .. }) => expr.spanless_eq(expr2),
+            (Self::Not { expr, .. }, Self::Not { expr: expr2, .. }) => expr.spanless_eq(expr2),
+            (
+                Self::BinOp {
+                    left, op, right, ..
+                },
+                Self::BinOp {
+                    left: left2,
+                    op: op2,
+                    right: right2,
+                    ..
+                },
+            ) => op == op2 && left.spanless_eq(left2) && right.spanless_eq(right2),
+            (Self::Error(_), Self::Error(_)) => true,
+            _ => false,
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use crate::span::{HasSpan, Span};
 
-use super::{Expr, Ident, Space};
+use super::{Expr, Ident, Space, SpanlessEq};
 
 #[derive(Debug, Clone)]
 pub enum Field {
@@ -98,3 +98,49 @@ impl Field {
         Expr::Field(self)
     }
 }
+
+impl SpanlessEq for Field {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                Self::Access { expr, index, .. },
+                Self::Access {
+                    expr: expr2,
+                    index: index2,
+                    ..
+                },
+            ) => expr.spanless_eq(expr2) && index.spanless_eq(index2),
+            (
+                Self::Assign {
+                    expr, index, value, ..
+                },
+                Self::Assign {
+                    expr: expr2,
+                    index: index2,
+                    value: value2,
+                    ..
+                },
+            ) => expr.spanless_eq(expr2) && index.spanless_eq(index2) && value.spanless_eq(value2),
+            (
+                Self::AccessIdent { expr, ident, .. },
+                Self::AccessIdent {
+                    expr: expr2,
+                    ident: ident2,
+                    ..
+                },
+            ) => expr.spanless_eq(expr2) && ident.spanless_eq(ident2),
+            (
+                Self::AssignIdent {
+                    expr, ident, value, ..
+                },
+                Self::AssignIdent {
+                    expr: expr2,
+                    ident: ident2,
+                    value: value2,
+                    ..
+                },
+            ) => expr.spanless_eq(expr2) && ident.spanless_eq(ident2) && value.spanless_eq(value2),
+            _ => false,
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use crate::span::{HasSpan, Span};
 
-use super::{Expr, Ident, Space, TablePattern};
+use super::{Expr, Ident, Space, SpanlessEq, TablePattern};
 
 #[derive(Debug, Clone)]
 pub enum FuncDef {
@@ -137,3 +137,83 @@ impl FuncDef {
         Expr::FuncDef(self)
     }
 }
+
+impl SpanlessEq for FuncDef {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::AnonNoArg { body, .. }, Self::AnonNoArg { body: body2, .. }) => {
+                body.spanless_eq(body2)
+            }
+            (
+                Self::AnonArg { arg, body, .. },
+                Self::AnonArg {
+                    arg: arg2,
+                    body: body2,
+                    ..
+                },
+            ) => arg.spanless_eq(arg2) && body.spanless_eq(body2),
+            (
+                Self::AnonDestr { pattern, body, .. },
+                Self::AnonDestr {
+                    pattern: pattern2,
+                    body: body2,
+                    ..
+                },
+            ) => pattern.spanless_eq(pattern2) && body.spanless_eq(body2),
+            (
+                Self::NamedNoArg {
+                    local, name, body, ..
+                },
+                Self::NamedNoArg {
+                    local: local2,
+                    name: name2,
+                    body: body2,
+                    ..
+                },
+            ) => local.spanless_eq(local2) && name.spanless_eq(name2) && body.spanless_eq(body2),
+            (
+                Self::NamedArg {
+                    local,
+                    name,
+                    arg,
+                    body,
+                    ..
+                },
+                Self::NamedArg {
+                    local: local2,
+                    name: name2,
+                    arg: arg2,
+                    body: body2,
+                    ..
+                },
+            ) => {
+                local.spanless_eq(local2)
+                    && name.spanless_eq(name2)
+                    && arg.spanless_eq(arg2)
+                    && body.spanless_eq(body2)
+            }
+            (
+                Self::NamedDestr {
+                    local,
+                    name,
+                    pattern,
+                    body,
+                    ..
+                },
+                Self::NamedDestr {
+                    local: local2,
+                    name: name2,
+                    pattern: pattern2,
+                    body: body2,
+                    ..
+                },
+            ) => {
+                local.spanless_eq(local2)
+                    && name.spanless_eq(name2)
+                    && pattern.spanless_eq(pattern2)
+                    && body.spanless_eq(body2)
+            }
+            _ => false,
+        }
+    }
+}
@@ -3,7 +3,7 @@ use std::fmt;
 use crate::builtin::Builtin;
 use crate::span::{HasSpan, Span};
 
-use super::{BoundedSeparated, Expr, Ident, Space};
+use super::{BoundedSeparated, Expr, Ident, Space, SpanlessEq};
 
 #[derive(Clone)]
 pub enum NumLitStr {
@@ -53,6 +53,33 @@ impl HasSpan for NumLit {
     }
 }
 
+/// Positive floating point number literal.
+///
+/// Only decimal notation is supported. Either a fractional part or an exponent
+/// is required. Underscores can be inserted before and after any digit.
+///
+/// - `1.5`
+/// - `0.25e-3`
+/// - `1e10`
+#[derive(Clone)]
+pub struct FloatLit {
+    pub value: f64,
+    pub str: String,
+    pub span: Span,
+}
+
+impl fmt::Debug for FloatLit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.str)
+    }
+}
+
+impl HasSpan for FloatLit {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
 #[derive(Clone)]
 pub enum StringLitElem {
     /// Normal unescaped characters
@@ -182,6 +209,9 @@ pub enum Lit {
     /// See [`NumLit`].
     Num(NumLit),
 
+    /// See [`FloatLit`].
+    Float(FloatLit),
+
     /// See [`StringLit`]
     String(StringLit),
 
@@ -196,6 +226,7 @@ impl fmt::Debug for Lit {
             Self::Bool(b, _) => write!(f, "l#{b:?}"),
             Self::Builtin(b, _) => write!(f, "l#{b:?}"),
             Self::Num(n) => write!(f, "l#{n:?}"),
+            Self::Float(n) => write!(f, "l#{n:?}"),
             Self::String(s) => {
                 write!(f, "l#")?;
                 s.fmt(f)
@@ -215,6 +246,7 @@ impl HasSpan for Lit {
             Self::Bool(_, span) => *span,
             Self::Builtin(_, span) => *span,
             Self::Num(n) => n.span(),
+            Self::Float(n) => n.span(),
             Self::String(s) => s.span(),
             Self::Table(t) => t.span(),
         }
@@ -226,3 +258,74 @@ impl Lit {
         Expr::Lit(self)
     }
 }
+
+impl SpanlessEq for NumLit {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        self.value == other.value && format!("{self:?}") == format!("{other:?}")
+    }
+}
+
+impl SpanlessEq for FloatLit {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        self.value == other.value && self.str == other.str
+    }
+}
+
+impl SpanlessEq for StringLit {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        // Escape sequences are compared by the text they produce, not by how
+        // they are written.
+        let text = |lit: &Self| -> String {
+            (lit.elems.iter())
+                .map(|elem| match elem {
+                    StringLitElem::Plain(str) => str.clone(),
+                    StringLitElem::Unicode(char) => char.to_string(),
+                    StringLitElem::Backslash => "\\".to_string(),
+                    StringLitElem::DoubleQuote => "\"".to_string(),
+                    StringLitElem::Tab => "\t".to_string(),
+                    StringLitElem::CarriageReturn => "\r".to_string(),
+                    StringLitElem::Newline => "\n".to_string(),
+                })
+                .collect()
+        };
+        text(self) == text(other)
+    }
+}
+
+impl SpanlessEq for TableLitElem {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Positional(value), Self::Positional(value2)) => value.spanless_eq(value2),
+            (
+                Self::Named { name, value, .. },
+                Self::Named {
+                    name: name2,
+                    value: value2,
+                    ..
+                },
+            ) => name.spanless_eq(name2) && value.spanless_eq(value2),
+            _ => false,
+        }
+    }
+}
+
+impl SpanlessEq for TableLit {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        self.0.spanless_eq(&other.0)
+    }
+}
+
+impl SpanlessEq for Lit {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Nil(_), Self::Nil(_)) => true,
+            (Self::Bool(bool, _), Self::Bool(bool2, _)) => bool == bool2,
+            (Self::Builtin(builtin, _), Self::Builtin(builtin2, _)) => builtin == builtin2,
+            (Self::Num(num), Self::Num(num2)) => num.spanless_eq(num2),
+            (Self::Float(float), Self::Float(float2)) => float.spanless_eq(float2),
+            (Self::String(string), Self::String(string2)) => string.spanless_eq(string2),
+            (Self::Table(table), Self::Table(table2)) => table.spanless_eq(table2),
+            _ => false,
+        }
+    }
+}
@@ -1,13 +1,13 @@
 use crate::span::{HasSpan, Span};
 
-use super::{BoundedSeparated, Expr, Space, TableLitElem};
+use super::{BoundedSeparated, Expr, Space, SpanlessEq, TableLitElem};
 
 #[derive(Debug, Clone)]
 pub enum Program {
     /// Structure: `s0 lit s1`
     Expr {
         s0: Space,
-        expr: Expr,
+        expr: Box<Expr>,
         s1: Space,
         span: Span,
     },
@@ -28,3 +28,15 @@ impl HasSpan for Program {
         }
     }
 }
+
+impl SpanlessEq for Program {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Expr { expr, .. }, Self::Expr { expr: expr2, .. }) => expr.spanless_eq(expr2),
+            (Self::Module { elems, .. }, Self::Module { elems: elems2, .. }) => {
+                elems.spanless_eq(elems2)
+            }
+            _ => false,
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use crate::span::{HasSpan, Span};
 
-use super::{BoundedSeparated, Expr, Ident, Space, TableLitElem};
+use super::{BoundedSeparated, Expr, Ident, Space, SpanlessEq, TableLitElem};
 
 #[derive(Debug, Clone)]
 pub enum TableConstrElem {
@@ -55,3 +55,26 @@ impl TableConstr {
         Expr::TableConstr(self)
     }
 }
+
+impl SpanlessEq for TableConstrElem {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Lit(lit), Self::Lit(lit2)) => lit.spanless_eq(lit2),
+            (
+                Self::Indexed { index, value, .. },
+                Self::Indexed {
+                    index: index2,
+                    value: value2,
+                    ..
+                },
+            ) => index.spanless_eq(index2) && value.spanless_eq(value2),
+            _ => false,
+        }
+    }
+}
+
+impl SpanlessEq for TableConstr {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        self.0.spanless_eq(&other.0)
+    }
+}
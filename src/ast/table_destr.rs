@@ -1,31 +1,85 @@
 use crate::span::{HasSpan, Span};
 
-use super::{BoundedSeparated, Expr, Ident, Space};
+use super::{BoundedSeparated, Expr, Ident, Space, SpanlessEq};
 
-// TODO Make table patterns recursive
+/// What a value is destructured into.
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    /// `foo`
+    Ident(Ident),
+
+    /// `{ foo, bar: baz }`
+    Table(TablePattern),
+}
+
+impl HasSpan for Pattern {
+    fn span(&self) -> Span {
+        match self {
+            Self::Ident(ident) => ident.span(),
+            Self::Table(pattern) => pattern.span(),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub enum TablePatternElem {
-    /// `foo`
-    Positional(Ident),
+    /// - `foo`
+    /// - `{ .. }`
+    Positional(Pattern),
 
-    /// `foo: bar`
+    /// - `foo = a`
+    /// - `{ .. } = a`
     ///
-    /// Structure: `name s0 : s1 ident`
+    /// Structure: `pattern s0 = s1 default`
+    PositionalDefault {
+        pattern: Pattern,
+        s0: Space,
+        s1: Space,
+        default: Box<Expr>,
+        span: Span,
+    },
+
+    /// - `foo: bar`
+    /// - `foo: { .. }`
+    ///
+    /// Structure: `name s0 : s1 pattern`
     Named {
         name: Ident,
         s0: Space,
         s1: Space,
-        ident: Ident,
+        pattern: Pattern,
         span: Span,
     },
+
+    /// - `foo: bar = a`
+    /// - `foo: { .. } = a`
+    ///
+    /// Structure: `name s0 : s1 pattern s2 = s3 default`
+    NamedDefault {
+        name: Ident,
+        s0: Space,
+        s1: Space,
+        pattern: Pattern,
+        s2: Space,
+        s3: Space,
+        default: Box<Expr>,
+        span: Span,
+    },
+
+    /// `..foo`
+    ///
+    /// Structure: `.. s0 name`
+    Rest { s0: Space, name: Ident, span: Span },
 }
 
 impl HasSpan for TablePatternElem {
     fn span(&self) -> Span {
         match self {
-            Self::Positional(ident) => ident.span(),
+            Self::Positional(pattern) => pattern.span(),
+            Self::PositionalDefault { span, .. } => *span,
             Self::Named { span, .. } => *span,
+            Self::NamedDefault { span, .. } => *span,
+            Self::Rest { span, .. } => *span,
         }
     }
 }
@@ -33,6 +87,8 @@ impl HasSpan for TablePatternElem {
 /// `{ foo, bar: baz }`
 ///
 /// Structure: `{ s0 elems s1 }`
+///
+/// At most one element may be a [`TablePatternElem::Rest`].
 #[derive(Debug, Clone)]
 pub struct TablePattern(pub BoundedSeparated<TablePatternElem>);
 
@@ -84,3 +140,75 @@ impl TableDestr {
         Expr::TableDestr(self)
     }
 }
+
+impl SpanlessEq for Pattern {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Ident(name), Self::Ident(name2)) => name.spanless_eq(name2),
+            (Self::Table(pattern), Self::Table(pattern2)) => pattern.spanless_eq(pattern2),
+            _ => false,
+        }
+    }
+}
+
+impl SpanlessEq for TablePatternElem {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Positional(pattern), Self::Positional(pattern2)) => {
+                pattern.spanless_eq(pattern2)
+            }
+            (
+                Self::PositionalDefault {
+                    pattern, default, ..
+                },
+                Self::PositionalDefault {
+                    pattern: pattern2,
+                    default: default2,
+                    ..
+                },
+            ) => pattern.spanless_eq(pattern2) && default.spanless_eq(default2),
+            (
+                Self::Named { name, pattern, .. },
+                Self::Named {
+                    name: name2,
+                    pattern: pattern2,
+                    ..
+                },
+            ) => name.spanless_eq(name2) && pattern.spanless_eq(pattern2),
+            (
+                Self::NamedDefault {
+                    name,
+                    pattern,
+                    default,
+                    ..
+                },
+                Self::NamedDefault {
+                    name: name2,
+                    pattern: pattern2,
+                    default: default2,
+                    ..
+                },
+            ) => {
+                name.spanless_eq(name2)
+                    && pattern.spanless_eq(pattern2)
+                    && default.spanless_eq(default2)
+            }
+            (Self::Rest { name, .. }, Self::Rest { name: name2, .. }) => name.spanless_eq(name2),
+            _ => false,
+        }
+    }
+}
+
+impl SpanlessEq for TablePattern {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        self.0.spanless_eq(&other.0)
+    }
+}
+
+impl SpanlessEq for TableDestr {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        self.local.spanless_eq(&other.local)
+            && self.pattern.spanless_eq(&other.pattern)
+            && self.value.spanless_eq(&other.value)
+    }
+}
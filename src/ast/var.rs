@@ -1,6 +1,6 @@
 use crate::span::{HasSpan, Span};
 
-use super::{Expr, Ident, Space};
+use super::{Expr, Ident, Space, SpanlessEq};
 
 #[derive(Debug, Clone)]
 pub enum Var {
@@ -107,3 +107,42 @@ impl Var {
         Expr::Var(self)
     }
 }
+
+impl SpanlessEq for Var {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Access { index, .. }, Self::Access { index: index2, .. }) => {
+                index.spanless_eq(index2)
+            }
+            (
+                Self::Assign {
+                    local,
+                    index,
+                    value,
+                    ..
+                },
+                Self::Assign {
+                    local: local2,
+                    index: index2,
+                    value: value2,
+                    ..
+                },
+            ) => {
+                local.spanless_eq(local2) && index.spanless_eq(index2) && value.spanless_eq(value2)
+            }
+            (Self::AccessIdent(name), Self::AccessIdent(name2)) => name.spanless_eq(name2),
+            (
+                Self::AssignIdent {
+                    local, name, value, ..
+                },
+                Self::AssignIdent {
+                    local: local2,
+                    name: name2,
+                    value: value2,
+                    ..
+                },
+            ) => local.spanless_eq(local2) && name.spanless_eq(name2) && value.spanless_eq(value2),
+            _ => false,
+        }
+    }
+}
@@ -1,3 +1,29 @@
+//! Turn syntactic sugar into literals, see the README for all the rules.
+//!
+//! Desugaring happens in small steps. [`Program::desugar`] applies exactly one
+//! [`Rule`] to the outermost, leftmost node that is not yet desugared and
+//! returns the [`Step`] it made, or `None` if there was nothing left to
+//! desugar. [`Program::desugar_fully`] makes all of these steps in a single
+//! walk over the program.
+//!
+//! The rules to apply can be limited to certain [`Kind`]s of constructs. Other
+//! constructs are left as they are, but their children are still desugared.
+//!
+//! Generated nodes reuse the span of the node they replace, so the spans of
+//! fully desugared code still point to the sugar they came from. The
+//! [`Provenance`] of a span records which rules produced the code there.
+//!
+//! [`Program::desugar`]: crate::ast::Program::desugar
+//! [`Program::desugar_fully`]: crate::ast::Program::desugar_fully
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use clap::ValueEnum;
+
+use crate::ast::{BinOp, Expr};
+use crate::span::Span;
+
 mod basic;
 mod call;
 mod expr;
@@ -8,3 +34,271 @@ mod program;
 mod table_constr;
 mod table_destr;
 mod var;
+
+/// The kinds of constructs that rules desugar, see [`Rule::kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+pub enum Kind {
+    Call,
+    Field,
+    Var,
+    TableConstr,
+    TableDestr,
+    FuncDef,
+    Paren,
+    Neg,
+    Not,
+    BinOp,
+    TableLit,
+    Module,
+}
+
+impl Kind {
+    pub fn all() -> HashSet<Self> {
+        Self::value_variants().iter().copied().collect()
+    }
+}
+
+/// A desugaring rule, named like the sugar in the README's tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    CallArg,
+    CallNoArg,
+    CallConstr,
+    FieldAccess,
+    FieldAssign,
+    FieldAccessIdent,
+    FieldAssignIdent,
+    VarAccess,
+    VarAssign,
+    VarAssignLocal,
+    VarAccessIdent,
+    VarAssignIdent,
+    VarAssignIdentLocal,
+    TableConstr,
+    TableConstrIndexed,
+    TableDestr,
+    TableDestrLocal,
+    FuncAnonNoArg,
+    FuncAnonArg,
+    FuncAnonDestr,
+    FuncNamedNoArg,
+    FuncNamedNoArgLocal,
+    FuncNamedArg,
+    FuncNamedArgLocal,
+    FuncNamedDestr,
+    FuncNamedDestrLocal,
+    Paren,
+    Neg,
+    Not,
+    BinOp(BinOp),
+    /// Removing `nil` values from table literals.
+    TableLitNil,
+    Module,
+}
+
+impl Rule {
+    /// What the sugar this rule applies to is called in error messages.
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::CallArg | Self::CallNoArg | Self::CallConstr => "call",
+            Self::FieldAccess | Self::FieldAccessIdent => "field access",
+            Self::FieldAssign | Self::FieldAssignIdent => "field assignment",
+            Self::VarAccess | Self::VarAccessIdent => "variable access",
+            Self::VarAssign
+            | Self::VarAssignLocal
+            | Self::VarAssignIdent
+            | Self::VarAssignIdentLocal => "variable assignment",
+            Self::TableConstr | Self::TableConstrIndexed => "table constructor",
+            Self::TableDestr | Self::TableDestrLocal => "destructuring",
+            Self::FuncAnonNoArg
+            | Self::FuncAnonArg
+            | Self::FuncAnonDestr
+            | Self::FuncNamedNoArg
+            | Self::FuncNamedNoArgLocal
+            | Self::FuncNamedArg
+            | Self::FuncNamedArgLocal
+            | Self::FuncNamedDestr
+            | Self::FuncNamedDestrLocal => "function definition",
+            Self::Paren => "parentheses",
+            Self::Neg | Self::Not | Self::BinOp(_) => "operator",
+            Self::TableLitNil => "table literal",
+            Self::Module => "module",
+        }
+    }
+
+    pub fn kind(self) -> Kind {
+        match self {
+            Self::CallArg | Self::CallNoArg | Self::CallConstr => Kind::Call,
+            Self::FieldAccess
+            | Self::FieldAssign
+            | Self::FieldAccessIdent
+            | Self::FieldAssignIdent => Kind::Field,
+            Self::VarAccess
+            | Self::VarAssign
+            | Self::VarAssignLocal
+            | Self::VarAccessIdent
+            | Self::VarAssignIdent
+            | Self::VarAssignIdentLocal => Kind::Var,
+            Self::TableConstr | Self::TableConstrIndexed => Kind::TableConstr,
+            Self::TableDestr | Self::TableDestrLocal => Kind::TableDestr,
+            Self::FuncAnonNoArg
+            | Self::FuncAnonArg
+            | Self::FuncAnonDestr
+            | Self::FuncNamedNoArg
+            | Self::FuncNamedNoArgLocal
+            | Self::FuncNamedArg
+            | Self::FuncNamedArgLocal
+            | Self::FuncNamedDestr
+            | Self::FuncNamedDestrLocal => Kind::FuncDef,
+            Self::Paren => Kind::Paren,
+            Self::Neg => Kind::Neg,
+            Self::Not => Kind::Not,
+            Self::BinOp(_) => Kind::BinOp,
+            Self::TableLitNil => Kind::TableLit,
+            Self::Module => Kind::Module,
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CallArg => write!(f, "call a(b)"),
+            Self::CallNoArg => write!(f, "call a()"),
+            Self::CallConstr => write!(f, "call a{{..}}"),
+            Self::FieldAccess => write!(f, "field a[b]"),
+            Self::FieldAssign => write!(f, "field a[b] = c"),
+            Self::FieldAccessIdent => write!(f, "field a.foo"),
+            Self::FieldAssignIdent => write!(f, "field a.foo = b"),
+            Self::VarAccess => write!(f, "var [a]"),
+            Self::VarAssign => write!(f, "var [a] = b"),
+            Self::VarAssignLocal => write!(f, "var local [a] = b"),
+            Self::VarAccessIdent => write!(f, "var foo"),
+            Self::VarAssignIdent => write!(f, "var foo = a"),
+            Self::VarAssignIdentLocal => write!(f, "var local foo = a"),
+            Self::TableConstr => write!(f, "constructor {{ a, b, foo: c }}"),
+            Self::TableConstrIndexed => write!(f, "constructor {{ .., [a]: b }}"),
+            Self::TableDestr => write!(f, "destructure {{..}} = a"),
+            Self::TableDestrLocal => write!(f, "destructure local {{..}} = a"),
+            Self::FuncAnonNoArg => write!(f, "function function() a"),
+            Self::FuncAnonArg => write!(f, "function function(foo) a"),
+            Self::FuncAnonDestr => write!(f, "function function{{..}} a"),
+            Self::FuncNamedNoArg => write!(f, "function function foo() a"),
+            Self::FuncNamedNoArgLocal => write!(f, "function local function foo() a"),
+            Self::FuncNamedArg => write!(f, "function function foo(a) b"),
+            Self::FuncNamedArgLocal => write!(f, "function local function foo(a) b"),
+            Self::FuncNamedDestr => write!(f, "function function foo{{..}} a"),
+            Self::FuncNamedDestrLocal => write!(f, "function local function foo{{..}} a"),
+            Self::Paren => write!(f, "parentheses (a)"),
+            Self::Neg => write!(f, "operator -a"),
+            Self::Not => write!(f, "operator not a"),
+            Self::BinOp(op) => write!(f, "operator a {} b", op.symbol()),
+            Self::TableLitNil => write!(f, "literal '{{ .., foo: nil }}"),
+            Self::Module => write!(f, "module"),
+        }
+    }
+}
+
+/// The application of a [`Rule`] to the node at `span`.
+#[derive(Debug, Clone)]
+pub struct Step {
+    pub rule: Rule,
+    pub span: Span,
+    /// Spans of generated nodes that don't reuse `span`, for example the
+    /// pattern of a destructuring.
+    pub generated: Vec<Span>,
+}
+
+impl Step {
+    pub fn new(rule: Rule, span: Span) -> Self {
+        Self {
+            rule,
+            span,
+            generated: vec![],
+        }
+    }
+
+    pub fn with_generated(mut self, generated: Vec<Span>) -> Self {
+        self.generated = generated;
+        self
+    }
+}
+
+/// The steps a [`Pass`] has made so far.
+enum Steps {
+    /// Stop after the first step.
+    First(Option<Step>),
+    /// Keep desugaring until no rule applies any more.
+    All(Provenance),
+}
+
+/// A walk over a program that desugars constructs of the given kinds.
+///
+/// A pass either stops after its first step, or desugars everything in one
+/// go. In the latter case, every node a rule produces is desugared again right
+/// away, before moving on to its siblings.
+struct Pass<'a> {
+    kinds: &'a HashSet<Kind>,
+    steps: Steps,
+}
+
+impl Pass<'_> {
+    /// Whether rules of this kind are applied.
+    fn applies(&self, kind: Kind) -> bool {
+        self.kinds.contains(&kind)
+    }
+
+    /// Whether the pass has made all the steps it is going to make.
+    fn done(&self) -> bool {
+        matches!(self.steps, Steps::First(Some(_)))
+    }
+
+    /// Record a step and return whether to keep desugaring the code it
+    /// produced.
+    fn record(&mut self, step: Step) -> bool {
+        debug_assert!(self.applies(step.rule.kind()));
+        match &mut self.steps {
+            Steps::First(first) => {
+                *first = Some(step);
+                false
+            }
+            Steps::All(provenance) => {
+                provenance.record(step);
+                true
+            }
+        }
+    }
+
+    /// Record a step that replaced a node by `new`, see [`Self::record`].
+    fn rewrite(&mut self, step: Step, new: Expr) -> Expr {
+        if self.record(step) {
+            new.desugar(self)
+        } else {
+            new
+        }
+    }
+}
+
+/// The rules that produced the code at each span, in the order they were
+/// applied.
+///
+/// The first rule of a chain was applied to the sugar the user wrote. Spans
+/// of nodes generated with a span of their own start out with the chain of
+/// the node they were generated from.
+#[derive(Debug, Default)]
+pub struct Provenance(HashMap<Span, Vec<(Rule, Span)>>);
+
+impl Provenance {
+    pub fn record(&mut self, step: Step) {
+        let mut chain = self.chain(step.span).to_vec();
+        chain.push((step.rule, step.span));
+        for span in step.generated {
+            self.0.entry(span).or_insert_with(|| chain.clone());
+        }
+        self.0.insert(step.span, chain);
+    }
+
+    pub fn chain(&self, span: Span) -> &[(Rule, Span)] {
+        self.0.get(&span).map_or(&[], |chain| chain)
+    }
+}
@@ -1,24 +1,26 @@
 use crate::ast::BoundedSeparated;
 
+use super::Pass;
+
 impl<E> BoundedSeparated<E> {
-    pub fn desugar(self, desugar_elem: impl Fn(E) -> (E, bool)) -> (Self, bool) {
-        let mut desugared = false;
+    pub(super) fn desugar(
+        self,
+        pass: &mut Pass<'_>,
+        desugar_elem: impl Fn(E, &mut Pass<'_>) -> E,
+    ) -> Self {
         let mut elems = vec![];
         for (s0, elem, s1) in self.elems {
-            if desugared {
+            if pass.done() {
                 elems.push((s0, elem, s1));
             } else {
-                let (elem, elem_desugared) = desugar_elem(elem);
-                desugared = desugared || elem_desugared;
-                elems.push((s0, elem, s1));
+                elems.push((s0, desugar_elem(elem, pass), s1));
             }
         }
 
-        let new = Self {
+        Self {
             elems,
             trailing: self.trailing,
             span: self.span,
-        };
-        (new, desugared)
+        }
     }
 }
@@ -1,9 +1,64 @@
 use crate::ast::{BoundedSeparated, Call, Expr, Ident, Lit, TableLitElem};
 
+use super::{Kind, Pass, Rule, Step};
+
 // TODO Add span for just the parentheses to ast, or limit span to parentheses
 
 impl Call {
-    pub fn desugar(self) -> (Expr, bool) {
+    fn desugar_children(self, pass: &mut Pass<'_>) -> Self {
+        match self {
+            Self::Arg {
+                expr,
+                s0,
+                s1,
+                arg,
+                s2,
+                span,
+            } => Self::Arg {
+                expr: expr.desugar_child(pass),
+                s0,
+                s1,
+                arg: arg.desugar_child(pass),
+                s2,
+                span,
+            },
+
+            Self::NoArg { expr, s0, s1, span } => Self::NoArg {
+                expr: expr.desugar_child(pass),
+                s0,
+                s1,
+                span,
+            },
+
+            // The constructor can't be replaced by a literal without the call
+            // being desugared, but its elements can be desugared.
+            Self::Constr {
+                expr,
+                s0,
+                constr,
+                span,
+            } => {
+                let expr = expr.desugar_child(pass);
+                let constr = if pass.done() {
+                    constr
+                } else {
+                    constr.desugar_elems(pass)
+                };
+                Self::Constr {
+                    expr,
+                    s0,
+                    constr,
+                    span,
+                }
+            }
+        }
+    }
+
+    pub(super) fn desugar(self, pass: &mut Pass<'_>) -> Expr {
+        if !pass.applies(Kind::Call) {
+            return self.desugar_children(pass).expr();
+        }
+
         match self {
             Self::Arg {
                 expr,
@@ -17,7 +72,7 @@ impl Call {
                     .then(TableLitElem::named(Ident::new("call", span), expr, span))
                     .then(TableLitElem::named(Ident::new("arg", span), arg, span))
                     .table_lit();
-                (new.lit().expr(), true)
+                pass.rewrite(Step::new(Rule::CallArg, span), new.lit().expr())
             }
 
             Self::NoArg {
@@ -27,7 +82,7 @@ impl Call {
                 span,
             } => {
                 let new = Self::arg(expr, Lit::Nil(span).expr().boxed(), span);
-                (new.expr(), true)
+                pass.rewrite(Step::new(Rule::CallNoArg, span), new.expr())
             }
 
             Self::Constr {
@@ -37,7 +92,7 @@ impl Call {
                 span,
             } => {
                 let new = Self::arg(expr, constr.expr().boxed(), span);
-                (new.expr(), true)
+                pass.rewrite(Step::new(Rule::CallConstr, span), new.expr())
             }
         }
     }
@@ -1,27 +1,65 @@
 use crate::ast::{BinOp, BoundedSeparated, Call, Expr, Lit, TableConstrElem};
 use crate::builtin::Builtin;
 
+use super::{Kind, Pass, Rule, Step};
+
 impl Expr {
-    pub fn desugar(self) -> (Self, bool) {
+    /// Desugar a child expression, unless an earlier child was already
+    /// desugared in this step.
+    pub(super) fn desugar_child(self: Box<Self>, pass: &mut Pass<'_>) -> Box<Self> {
+        if pass.done() {
+            return self;
+        }
+        self.desugar(pass).boxed()
+    }
+
+    pub(super) fn desugar(self, pass: &mut Pass<'_>) -> Self {
         match self {
-            Self::Lit(lit) => {
-                let (lit, desugared) = lit.desugar();
-                (lit.expr(), desugared)
-            }
+            Self::Lit(lit) => lit.desugar(pass).expr(),
+
+            Self::Call(call) => call.desugar(pass),
+            Self::Field(field) => field.desugar(pass),
+            Self::Var(var) => var.desugar(pass),
+            Self::TableConstr(constr) => constr.desugar(pass),
+            Self::TableDestr(destr) => destr.desugar(pass),
+            Self::FuncDef(def) => def.desugar(pass),
 
-            Self::Call(call) => call.desugar(),
-            Self::Field(field) => field.desugar(),
-            Self::Var(var) => var.desugar(),
-            Self::TableConstr(constr) => constr.desugar(),
-            Self::TableDestr(destr) => destr.desugar(),
-            Self::FuncDef(def) => def.desugar(),
+            Self::Paren {
+                s0,
+                inner,
+                s1,
+                span,
+            } if !pass.applies(Kind::Paren) => {
+                let inner = inner.desugar(pass);
+                Self::Paren {
+                    s0,
+                    inner: inner.boxed(),
+                    s1,
+                    span,
+                }
+            }
 
             Self::Paren {
                 s0: _,
                 inner,
                 s1: _,
-                span: _,
-            } => (*inner, true),
+                span,
+            } => pass.rewrite(Step::new(Rule::Paren, span), *inner),
+
+            Self::Neg {
+                minus,
+                s0,
+                expr,
+                span,
+            } if !pass.applies(Kind::Neg) => {
+                let expr = expr.desugar(pass);
+                Self::Neg {
+                    minus,
+                    s0,
+                    expr: expr.boxed(),
+                    span,
+                }
+            }
 
             Self::Neg {
                 minus,
@@ -30,7 +68,22 @@ impl Expr {
                 span,
             } => {
                 let new = Call::arg(Lit::Builtin(Builtin::Neg, minus).expr().boxed(), expr, span);
-                (new.expr(), true)
+                pass.rewrite(Step::new(Rule::Neg, span), new.expr())
+            }
+
+            Self::Not {
+                not,
+                s0,
+                expr,
+                span,
+            } if !pass.applies(Kind::Not) => {
+                let expr = expr.desugar(pass);
+                Self::Not {
+                    not,
+                    s0,
+                    expr: expr.boxed(),
+                    span,
+                }
             }
 
             Self::Not {
@@ -40,7 +93,27 @@ impl Expr {
                 span,
             } => {
                 let new = Call::arg(Lit::Builtin(Builtin::Not, not).expr().boxed(), expr, span);
-                (new.expr(), true)
+                pass.rewrite(Step::new(Rule::Not, span), new.expr())
+            }
+
+            Self::BinOp {
+                left,
+                s0,
+                op,
+                s1,
+                right,
+                span,
+            } if !pass.applies(Kind::BinOp) => {
+                let left = left.desugar_child(pass);
+                let right = right.desugar_child(pass);
+                Self::BinOp {
+                    left,
+                    s0,
+                    op,
+                    s1,
+                    right,
+                    span,
+                }
             }
 
             Self::BinOp {
@@ -71,8 +144,10 @@ impl Expr {
                     .then(TableConstrElem::positional(right))
                     .table_constr();
                 let new = Call::constr(Lit::Builtin(builtin, span).expr().boxed(), constr, span);
-                (new.expr(), true)
+                pass.rewrite(Step::new(Rule::BinOp(op), span), new.expr())
             }
+
+            Self::Error(span) => Self::Error(span),
         }
     }
 }
@@ -1,8 +1,90 @@
 use crate::ast::{BoundedSeparated, Call, Expr, Field, Lit, StringLit, TableConstrElem};
 use crate::builtin::Builtin;
 
+use super::{Kind, Pass, Rule, Step};
+
 impl Field {
-    pub fn desugar(self) -> (Expr, bool) {
+    fn desugar_children(self, pass: &mut Pass<'_>) -> Self {
+        match self {
+            Self::Access {
+                expr,
+                s0,
+                s1,
+                index,
+                s2,
+                span,
+            } => Self::Access {
+                expr: expr.desugar_child(pass),
+                s0,
+                s1,
+                index: index.desugar_child(pass),
+                s2,
+                span,
+            },
+
+            Self::Assign {
+                expr,
+                s0,
+                s1,
+                index,
+                s2,
+                s3,
+                s4,
+                value,
+                span,
+            } => Self::Assign {
+                expr: expr.desugar_child(pass),
+                s0,
+                s1,
+                index: index.desugar_child(pass),
+                s2,
+                s3,
+                s4,
+                value: value.desugar_child(pass),
+                span,
+            },
+
+            Self::AccessIdent {
+                expr,
+                s0,
+                s1,
+                ident,
+                span,
+            } => Self::AccessIdent {
+                expr: expr.desugar_child(pass),
+                s0,
+                s1,
+                ident,
+                span,
+            },
+
+            Self::AssignIdent {
+                expr,
+                s0,
+                s1,
+                ident,
+                s2,
+                s3,
+                value,
+                span,
+            } => Self::AssignIdent {
+                expr: expr.desugar_child(pass),
+                s0,
+                s1,
+                ident,
+                s2,
+                s3,
+                value: value.desugar_child(pass),
+                span,
+            },
+        }
+    }
+
+    pub(super) fn desugar(self, pass: &mut Pass<'_>) -> Expr {
+        if !pass.applies(Kind::Field) {
+            return self.desugar_children(pass).expr();
+        }
+
         match self {
             Self::Access {
                 expr,
@@ -21,7 +103,7 @@ impl Field {
                     constr,
                     span,
                 );
-                (new.expr(), true)
+                pass.rewrite(Step::new(Rule::FieldAccess, span), new.expr())
             }
 
             Self::Assign {
@@ -45,7 +127,7 @@ impl Field {
                     constr,
                     span,
                 );
-                (new.expr(), true)
+                pass.rewrite(Step::new(Rule::FieldAssign, span), new.expr())
             }
 
             Self::AccessIdent {
@@ -60,7 +142,7 @@ impl Field {
                     StringLit::from_ident(ident).lit().expr().boxed(),
                     span,
                 );
-                (new.expr(), true)
+                pass.rewrite(Step::new(Rule::FieldAccessIdent, span), new.expr())
             }
 
             Self::AssignIdent {
@@ -79,7 +161,7 @@ impl Field {
                     value,
                     span,
                 );
-                (new.expr(), true)
+                pass.rewrite(Step::new(Rule::FieldAssignIdent, span), new.expr())
             }
         }
     }
@@ -4,8 +4,134 @@ use crate::ast::{
 };
 use crate::builtin::Builtin;
 
+use super::{Kind, Pass, Rule, Step};
+
 impl FuncDef {
-    pub fn desugar(self) -> (Expr, bool) {
+    fn desugar_children(self, pass: &mut Pass<'_>) -> Self {
+        match self {
+            Self::AnonNoArg {
+                s0,
+                s1,
+                s2,
+                body,
+                span,
+            } => Self::AnonNoArg {
+                s0,
+                s1,
+                s2,
+                body: body.desugar_child(pass),
+                span,
+            },
+
+            Self::AnonArg {
+                s0,
+                s1,
+                arg,
+                s2,
+                s3,
+                body,
+                span,
+            } => Self::AnonArg {
+                s0,
+                s1,
+                arg,
+                s2,
+                s3,
+                body: body.desugar_child(pass),
+                span,
+            },
+
+            Self::AnonDestr {
+                s0,
+                pattern,
+                s1,
+                body,
+                span,
+            } => {
+                let pattern = pattern.desugar(pass);
+                Self::AnonDestr {
+                    s0,
+                    pattern,
+                    s1,
+                    body: body.desugar_child(pass),
+                    span,
+                }
+            }
+
+            Self::NamedNoArg {
+                local,
+                s0,
+                name,
+                s1,
+                s2,
+                s3,
+                body,
+                span,
+            } => Self::NamedNoArg {
+                local,
+                s0,
+                name,
+                s1,
+                s2,
+                s3,
+                body: body.desugar_child(pass),
+                span,
+            },
+
+            Self::NamedArg {
+                local,
+                s0,
+                name,
+                s1,
+                s2,
+                arg,
+                s3,
+                s4,
+                body,
+                span,
+            } => Self::NamedArg {
+                local,
+                s0,
+                name,
+                s1,
+                s2,
+                arg,
+                s3,
+                s4,
+                body: body.desugar_child(pass),
+                span,
+            },
+
+            Self::NamedDestr {
+                local,
+                s0,
+                name,
+                s1,
+                pattern,
+                s2,
+                body,
+                span,
+            } => {
+                let pattern = pattern.desugar(pass);
+                Self::NamedDestr {
+                    local,
+                    s0,
+                    name,
+                    s1,
+                    pattern,
+                    s2,
+                    body: body.desugar_child(pass),
+                    span,
+                }
+            }
+        }
+    }
+
+    pub(super) fn desugar(self, pass: &mut Pass<'_>) -> Expr {
+        if !pass.applies(Kind::FuncDef) {
+            return self.desugar_children(pass).expr();
+        }
+
         match self {
             Self::AnonNoArg {
                 s0: _,
@@ -26,7 +152,7 @@ impl FuncDef {
                         span,
                     ))
                     .table_constr();
-                (new.expr(), true)
+                pass.rewrite(Step::new(Rule::FuncAnonNoArg, span), new.expr())
             }
 
             Self::AnonArg {
@@ -51,7 +177,7 @@ impl FuncDef {
                     body: body.lit().expr().boxed(),
                     span,
                 };
-                (new.expr(), true)
+                pass.rewrite(Step::new(Rule::FuncAnonArg, span), new.expr())
             }
 
             Self::AnonDestr {
@@ -74,7 +200,7 @@ impl FuncDef {
                     body: body.lit().expr().boxed(),
                     span,
                 };
-                (new.expr(), true)
+                pass.rewrite(Step::new(Rule::FuncAnonDestr, span), new.expr())
             }
 
             Self::NamedNoArg {
@@ -87,9 +213,14 @@ impl FuncDef {
                 body,
                 span,
             } => {
+                let rule = if local.is_some() {
+                    Rule::FuncNamedNoArgLocal
+                } else {
+                    Rule::FuncNamedNoArg
+                };
                 let anon = Self::anon_no_arg(body, span);
                 let new = Var::assign_ident(local.is_some(), name, anon.expr().boxed(), span);
-                (new.expr(), true)
+                pass.rewrite(Step::new(rule, span), new.expr())
             }
 
             Self::NamedArg {
@@ -104,9 +235,14 @@ impl FuncDef {
                 body,
                 span,
             } => {
+                let rule = if local.is_some() {
+                    Rule::FuncNamedArgLocal
+                } else {
+                    Rule::FuncNamedArg
+                };
                 let anon = Self::anon_arg(arg, body, span);
                 let new = Var::assign_ident(local.is_some(), name, anon.expr().boxed(), span);
-                (new.expr(), true)
+                pass.rewrite(Step::new(rule, span), new.expr())
             }
 
             Self::NamedDestr {
@@ -119,9 +255,14 @@ impl FuncDef {
                 body,
                 span,
             } => {
+                let rule = if local.is_some() {
+                    Rule::FuncNamedDestrLocal
+                } else {
+                    Rule::FuncNamedDestr
+                };
                 let anon = Self::anon_destr(pattern, body, span);
                 let new = Var::assign_ident(local.is_some(), name, anon.expr().boxed(), span);
-                (new.expr(), true)
+                pass.rewrite(Step::new(rule, span), new.expr())
             }
         }
     }
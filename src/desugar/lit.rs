@@ -1,12 +1,11 @@
 use crate::ast::{Expr, Lit, TableLit, TableLitElem};
 
+use super::{Kind, Pass, Rule, Step};
+
 impl TableLitElem {
-    pub fn desugar(self) -> (Self, bool) {
+    pub(super) fn desugar(self, pass: &mut Pass<'_>) -> Self {
         match self {
-            Self::Positional(expr) => {
-                let (expr, desugared) = expr.desugar();
-                (Self::Positional(expr.boxed()), desugared)
-            }
+            Self::Positional(expr) => Self::Positional(expr.desugar(pass).boxed()),
 
             Self::Named {
                 name,
@@ -15,22 +14,27 @@ impl TableLitElem {
                 value,
                 span,
             } => {
-                let (value, desugared) = value.desugar();
-                let new = Self::Named {
+                let value = value.desugar(pass);
+                Self::Named {
                     name,
                     s0,
                     s1,
                     value: value.boxed(),
                     span,
-                };
-                (new, desugared)
+                }
             }
         }
     }
 }
 
 impl TableLit {
-    pub fn desugar(self) -> (Self, bool) {
+    pub(super) fn desugar(self, pass: &mut Pass<'_>) -> Self {
+        if !pass.applies(Kind::TableLit) {
+            let elems = self.0.desugar(pass, |e, pass| e.desugar(pass));
+            return elems.table_lit();
+        }
+
+        let span = self.0.span;
         let (elems, removed) = self.0.remove_map(|e| match e {
             TableLitElem::Named { value, .. } if matches!(*value, Expr::Lit(Lit::Nil(_))) => {
                 Err(())
@@ -38,23 +42,24 @@ impl TableLit {
             e => Ok(e),
         });
         if removed.is_empty() {
-            let (elems, desugared) = elems.desugar(|e| e.desugar());
-            (elems.table_lit(), desugared)
+            elems.desugar(pass, |e, pass| e.desugar(pass)).table_lit()
         } else {
-            (elems.table_lit(), true)
+            let new = elems.table_lit();
+            if pass.record(Step::new(Rule::TableLitNil, span)) {
+                new.desugar(pass)
+            } else {
+                new
+            }
         }
     }
 }
 
 impl Lit {
-    pub fn desugar(self) -> (Self, bool) {
+    pub(super) fn desugar(self, pass: &mut Pass<'_>) -> Self {
         match self {
-            Self::Table(table) => {
-                let (table, desugared) = table.desugar();
-                (table.lit(), desugared)
-            }
+            Self::Table(table) => table.desugar(pass).lit(),
 
-            lit => (lit, false),
+            lit => lit,
         }
     }
 }
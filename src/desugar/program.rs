@@ -1,12 +1,51 @@
+use std::collections::HashSet;
+
 use crate::ast::{Program, Space};
 
+use super::{Kind, Pass, Provenance, Rule, Step, Steps};
+
 impl Program {
-    pub fn desugar(self) -> (Self, bool) {
+    /// Apply a single rule of the given kinds, see the [module
+    /// docs](super).
+    pub fn desugar(self, kinds: &HashSet<Kind>) -> (Self, Option<Step>) {
+        let mut pass = Pass {
+            kinds,
+            steps: Steps::First(None),
+        };
+        let new = self.desugar_pass(&mut pass);
+        match pass.steps {
+            Steps::First(step) => (new, step),
+            Steps::All(_) => unreachable!(),
+        }
+    }
+
+    /// Desugar until no rule of the given kinds applies any more. With
+    /// [`Kind::all`], only literals are left.
+    ///
+    /// This makes the same steps as calling [`Self::desugar`] repeatedly, but
+    /// walks the program only once.
+    pub fn desugar_fully(self, kinds: &HashSet<Kind>) -> (Self, Provenance) {
+        let mut pass = Pass {
+            kinds,
+            steps: Steps::All(Provenance::default()),
+        };
+        let new = self.desugar_pass(&mut pass);
+        match pass.steps {
+            Steps::All(provenance) => (new, provenance),
+            Steps::First(_) => unreachable!(),
+        }
+    }
+
+    fn desugar_pass(self, pass: &mut Pass<'_>) -> Self {
         match self {
             Self::Expr { s0, expr, s1, span } => {
-                let (expr, desugared) = expr.desugar();
-                let new = Self::Expr { s0, expr, s1, span };
-                (new, desugared)
+                let expr = expr.desugar_child(pass);
+                Self::Expr { s0, expr, s1, span }
+            }
+
+            Self::Module { s0, elems, span } if !pass.applies(Kind::Module) => {
+                let elems = elems.desugar(pass, |e, pass| e.desugar(pass));
+                Self::Module { s0, elems, span }
             }
 
             Self::Module { s0, elems, span } => {
@@ -14,11 +53,15 @@ impl Program {
                 // -> `s0 table`
                 let new = Self::Expr {
                     s0,
-                    expr: elems.table_lit().lit().expr(),
+                    expr: elems.table_lit().lit().expr().boxed(),
                     s1: Space::empty(span),
                     span,
                 };
-                (new, true)
+                if pass.record(Step::new(Rule::Module, span)) {
+                    new.desugar_pass(pass)
+                } else {
+                    new
+                }
             }
         }
     }
@@ -3,8 +3,47 @@ use crate::ast::{
 };
 use crate::span::HasSpan;
 
+use super::{Kind, Pass, Rule, Step};
+
+impl TableConstrElem {
+    pub(super) fn desugar(self, pass: &mut Pass<'_>) -> Self {
+        match self {
+            Self::Lit(lit) => Self::Lit(lit.desugar(pass)),
+
+            Self::Indexed {
+                s0,
+                index,
+                s1,
+                s2,
+                s3,
+                value,
+                span,
+            } => Self::Indexed {
+                s0,
+                index: index.desugar_child(pass),
+                s1,
+                s2,
+                s3,
+                value: value.desugar_child(pass),
+                span,
+            },
+        }
+    }
+}
+
 impl TableConstr {
-    pub fn desugar(self) -> (Expr, bool) {
+    /// Desugar the elements, but keep the constructor itself.
+    pub(super) fn desugar_elems(self, pass: &mut Pass<'_>) -> Self {
+        self.0
+            .desugar(pass, |e, pass| e.desugar(pass))
+            .table_constr()
+    }
+
+    pub(super) fn desugar(self, pass: &mut Pass<'_>) -> Expr {
+        if !pass.applies(Kind::TableConstr) {
+            return self.desugar_elems(pass).expr();
+        }
+
         let span = self.span();
 
         let (elems, setters) = self.0.remove_map(|e| match e {
@@ -20,6 +59,12 @@ impl TableConstr {
             } => Err((index, value, span)),
         });
 
+        let rule = if setters.is_empty() {
+            Rule::TableConstr
+        } else {
+            Rule::TableConstrIndexed
+        };
+
         let mut expr = BoundedSeparated::new(span)
             .then(TableLitElem::named(
                 Ident::new("raw", span),
@@ -30,10 +75,12 @@ impl TableConstr {
             .lit()
             .expr();
 
+        let mut generated = vec![];
         for (_, (index, value, span), _) in setters {
             expr = Field::assign(expr.boxed(), index, value, span).expr();
+            generated.push(span);
         }
 
-        (expr, true)
+        pass.rewrite(Step::new(rule, span).with_generated(generated), expr)
     }
 }
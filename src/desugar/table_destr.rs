@@ -1,36 +1,236 @@
 use crate::ast::{
-    BoundedSeparated, Call, Expr, Ident, Lit, StringLit, TableConstr, TableConstrElem, TableDestr,
-    TableLitElem, TablePattern, TablePatternElem,
+    BoundedSeparated, Call, Expr, FuncDef, Ident, Lit, Pattern, StringLit, TableConstr,
+    TableConstrElem, TableDestr, TableLitElem, TablePattern, TablePatternElem,
 };
 use crate::builtin::Builtin;
+use crate::span::{HasSpan, Span};
 
-fn pattern_to_constr(pattern: TablePattern) -> TableConstr {
-    pattern
-        .0
-        .map(|e| match e {
-            TablePatternElem::Positional(ident) => {
-                TableConstrElem::positional(StringLit::from_ident(ident).lit().expr().boxed())
+use super::{Kind, Pass, Rule, Step};
+
+/// The value a pattern table maps a key to.
+///
+/// Variables without default become their name as a string. Everything else
+/// becomes a table describing what to do with the value:
+///
+/// - `name`: the variable to assign the value to, or
+/// - `pattern` and `rest`: the nested pattern to destructure the value with,
+/// - `default`: a function returning the value to use if the key is missing.
+///
+/// The spans of generated tables are added to `generated`.
+fn pattern_target(
+    pattern: Pattern,
+    default: Option<Box<Expr>>,
+    span: Span,
+    generated: &mut Vec<Span>,
+) -> Expr {
+    let mut target = BoundedSeparated::new(span);
+    match pattern {
+        Pattern::Ident(ident) if default.is_none() => {
+            return StringLit::from_ident(ident).lit().expr();
+        }
+        Pattern::Ident(ident) => {
+            target = target.then(TableConstrElem::named(
+                Ident::new("name", span),
+                StringLit::from_ident(ident).lit().expr().boxed(),
+                span,
+            ));
+        }
+        Pattern::Table(pattern) => {
+            let (pattern, rest) = pattern_to_constr(pattern, generated);
+            target = target.then(TableConstrElem::named(
+                Ident::new("pattern", span),
+                pattern.expr().boxed(),
+                span,
+            ));
+            if let Some(rest) = rest {
+                target = target.then(rest);
             }
+        }
+    }
+
+    if let Some(default) = default {
+        let function = FuncDef::anon_no_arg(default, span);
+        target = target.then(TableConstrElem::named(
+            Ident::new("default", span),
+            function.expr().boxed(),
+            span,
+        ));
+    }
 
-            TablePatternElem::Named {
+    generated.push(span);
+    target.table_constr().expr()
+}
+
+/// Convert a pattern into a pattern table and a `rest` element naming the
+/// variable for the remaining entries, if any.
+///
+/// The spans of generated tables are added to `generated`.
+fn pattern_to_constr(
+    pattern: TablePattern,
+    generated: &mut Vec<Span>,
+) -> (TableConstr, Option<TableConstrElem>) {
+    generated.push(pattern.span());
+    let (elems, rests) = pattern.0.remove_map(|e| match e {
+        TablePatternElem::Positional(pattern) => {
+            let span = pattern.span();
+            Ok(TableConstrElem::positional(
+                pattern_target(pattern, None, span, generated).boxed(),
+            ))
+        }
+
+        TablePatternElem::PositionalDefault {
+            pattern,
+            s0: _,
+            s1: _,
+            default,
+            span,
+        } => Ok(TableConstrElem::positional(
+            pattern_target(pattern, Some(default), span, generated).boxed(),
+        )),
+
+        TablePatternElem::Named {
+            name,
+            s0,
+            s1,
+            pattern,
+            span,
+        } => Ok(TableConstrElem::Lit(TableLitElem::Named {
+            name,
+            s0,
+            s1,
+            value: pattern_target(pattern, None, span, generated).boxed(),
+            span,
+        })),
+
+        TablePatternElem::NamedDefault {
+            name,
+            s0,
+            s1,
+            pattern,
+            s2: _,
+            s3: _,
+            default,
+            span,
+        } => Ok(TableConstrElem::Lit(TableLitElem::Named {
+            name,
+            s0,
+            s1,
+            value: pattern_target(pattern, Some(default), span, generated).boxed(),
+            span,
+        })),
+
+        TablePatternElem::Rest { s0: _, name, span } => Err((name, span)),
+    });
+
+    // The parser ensures that there is at most one rest element.
+    let rest = rests.into_iter().next().map(|(_, (name, span), _)| {
+        TableConstrElem::named(
+            Ident::new("rest", span),
+            StringLit::from_ident(name).lit().expr().boxed(),
+            span,
+        )
+    });
+    (elems.table_constr(), rest)
+}
+
+impl TablePatternElem {
+    /// Desugar the default values.
+    fn desugar(self, pass: &mut Pass<'_>) -> Self {
+        match self {
+            Self::Positional(pattern) => Self::Positional(pattern.desugar(pass)),
+
+            Self::PositionalDefault {
+                pattern,
+                s0,
+                s1,
+                default,
+                span,
+            } => {
+                let pattern = pattern.desugar(pass);
+                Self::PositionalDefault {
+                    pattern,
+                    s0,
+                    s1,
+                    default: default.desugar_child(pass),
+                    span,
+                }
+            }
+
+            Self::Named {
                 name,
                 s0,
                 s1,
-                ident,
+                pattern,
                 span,
-            } => TableConstrElem::Lit(TableLitElem::Named {
+            } => {
+                let pattern = pattern.desugar(pass);
+                Self::Named {
+                    name,
+                    s0,
+                    s1,
+                    pattern,
+                    span,
+                }
+            }
+
+            Self::NamedDefault {
                 name,
                 s0,
                 s1,
-                value: StringLit::from_ident(ident).lit().expr().boxed(),
+                pattern,
+                s2,
+                s3,
+                default,
                 span,
-            }),
-        })
-        .table_constr()
+            } => {
+                let pattern = pattern.desugar(pass);
+                Self::NamedDefault {
+                    name,
+                    s0,
+                    s1,
+                    pattern,
+                    s2,
+                    s3,
+                    default: default.desugar_child(pass),
+                    span,
+                }
+            }
+
+            rest @ Self::Rest { .. } => rest,
+        }
+    }
+}
+
+impl Pattern {
+    /// Desugar the default values.
+    fn desugar(self, pass: &mut Pass<'_>) -> Self {
+        match self {
+            Self::Ident(ident) => Self::Ident(ident),
+            Self::Table(pattern) => Self::Table(pattern.desugar(pass)),
+        }
+    }
+}
+
+impl TablePattern {
+    /// Desugar the default values.
+    pub(super) fn desugar(self, pass: &mut Pass<'_>) -> Self {
+        let elems = self.0.desugar(pass, |e, pass| e.desugar(pass));
+        Self(elems)
+    }
 }
 
 impl TableDestr {
-    pub fn desugar(self) -> (Expr, bool) {
+    pub(super) fn desugar(self, pass: &mut Pass<'_>) -> Expr {
+        if !pass.applies(Kind::TableDestr) {
+            let pattern = self.pattern.desugar(pass);
+            let new = Self {
+                pattern,
+                value: self.value.desugar_child(pass),
+                ..self
+            };
+            return new.expr();
+        }
+
         let Self {
             local,
             pattern,
@@ -40,11 +240,21 @@ impl TableDestr {
             span,
         } = self;
 
+        let rule = if local.is_some() {
+            Rule::TableDestrLocal
+        } else {
+            Rule::TableDestr
+        };
+        let scope = Call::no_arg(Lit::Builtin(Builtin::Scope, span).expr().boxed(), span);
+        let mut generated = vec![];
+        let (pattern, rest) = pattern_to_constr(pattern, &mut generated);
         let mut constr = BoundedSeparated::new(span)
-            .then(TableConstrElem::positional(
-                pattern_to_constr(pattern).expr().boxed(),
-            ))
+            .then(TableConstrElem::positional(scope.expr().boxed()))
+            .then(TableConstrElem::positional(pattern.expr().boxed()))
             .then(TableConstrElem::positional(value));
+        if let Some(rest) = rest {
+            constr = constr.then(rest);
+        }
         if local.is_some() {
             constr = constr.then(TableConstrElem::named(
                 Ident::new("local", span),
@@ -58,6 +268,7 @@ impl TableDestr {
             constr.table_constr(),
             span,
         );
-        (new.expr(), true)
+        let step = Step::new(rule, span).with_generated(generated);
+        pass.rewrite(step, new.expr())
     }
 }
@@ -2,8 +2,68 @@ use crate::ast::{BoundedSeparated, Call, Expr, Field, Lit, StringLit, TableConst
 use crate::builtin::Builtin;
 use crate::span::HasSpan;
 
+use super::{Kind, Pass, Rule, Step};
+
 impl Var {
-    pub fn desugar(self) -> (Expr, bool) {
+    fn desugar_children(self, pass: &mut Pass<'_>) -> Self {
+        match self {
+            Self::Access {
+                s0,
+                index,
+                s1,
+                span,
+            } => Self::Access {
+                s0,
+                index: index.desugar_child(pass),
+                s1,
+                span,
+            },
+
+            Self::Assign {
+                local,
+                s0,
+                index,
+                s1,
+                s2,
+                s3,
+                value,
+                span,
+            } => Self::Assign {
+                local,
+                s0,
+                index: index.desugar_child(pass),
+                s1,
+                s2,
+                s3,
+                value: value.desugar_child(pass),
+                span,
+            },
+
+            Self::AccessIdent(name) => Self::AccessIdent(name),
+
+            Self::AssignIdent {
+                local,
+                name,
+                s0,
+                s1,
+                value,
+                span,
+            } => Self::AssignIdent {
+                local,
+                name,
+                s0,
+                s1,
+                value: value.desugar_child(pass),
+                span,
+            },
+        }
+    }
+
+    pub(super) fn desugar(self, pass: &mut Pass<'_>) -> Expr {
+        if !pass.applies(Kind::Var) {
+            return self.desugar_children(pass).expr();
+        }
+
         match self {
             Self::Access {
                 s0: _,
@@ -13,7 +73,7 @@ impl Var {
             } => {
                 let scope = Call::no_arg(Lit::Builtin(Builtin::Scope, span).expr().boxed(), span);
                 let new = Field::access(scope.expr().boxed(), index, span);
-                (new.expr(), true)
+                pass.rewrite(Step::new(Rule::VarAccess, span), new.expr())
             }
 
             Self::Assign {
@@ -28,7 +88,7 @@ impl Var {
             } => {
                 let scope = Call::no_arg(Lit::Builtin(Builtin::Scope, span).expr().boxed(), span);
                 let new = Field::assign(scope.expr().boxed(), index, value, span);
-                (new.expr(), true)
+                pass.rewrite(Step::new(Rule::VarAssign, span), new.expr())
             }
 
             Self::Assign {
@@ -52,13 +112,13 @@ impl Var {
                     constr,
                     span,
                 );
-                (new.expr(), true)
+                pass.rewrite(Step::new(Rule::VarAssignLocal, span), new.expr())
             }
 
             Self::AccessIdent(name) => {
                 let span = name.span();
                 let new = Self::access(StringLit::from_ident(name).lit().expr().boxed(), span);
-                (new.expr(), true)
+                pass.rewrite(Step::new(Rule::VarAccessIdent, span), new.expr())
             }
 
             Self::AssignIdent {
@@ -69,13 +129,18 @@ impl Var {
                 value,
                 span,
             } => {
+                let rule = if local.is_some() {
+                    Rule::VarAssignIdentLocal
+                } else {
+                    Rule::VarAssignIdent
+                };
                 let new = Self::assign(
                     local.is_some(),
                     StringLit::from_ident(name).lit().expr().boxed(),
                     value,
                     span,
                 );
-                (new.expr(), true)
+                pass.rewrite(Step::new(rule, span), new.expr())
             }
         }
     }
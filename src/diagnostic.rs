@@ -0,0 +1,138 @@
+//! Human-readable error reports that point into the source code.
+//!
+//! A [`Diagnostic`] is rendered similar to rustc's errors:
+//!
+//! ```text
+//! error: unexpected '}'
+//!  --> example.tada:2:9
+//!   |
+//! 2 |     a = }
+//!   |         ^
+//!   = expected one of '"', '\'', '(', '-', '[', '{'
+//! ```
+
+use std::fmt::Write as _;
+
+use chumsky::error::SimpleReason;
+
+use crate::parser::Error as ParseError;
+use crate::source::SourceFile;
+use crate::span::Span;
+
+#[derive(Debug)]
+pub struct Diagnostic {
+    msg: String,
+    span: Option<Span>,
+    /// Notes may point to a location of their own.
+    notes: Vec<(String, Option<Span>)>,
+}
+
+fn describe_char(c: Option<&char>) -> String {
+    match c {
+        Some(c) => format!("{c:?}"),
+        None => "end of input".to_string(),
+    }
+}
+
+impl Diagnostic {
+    pub fn new<S: ToString>(msg: S) -> Self {
+        Self {
+            msg: msg.to_string(),
+            span: None,
+            notes: vec![],
+        }
+    }
+
+    pub fn at(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    pub fn note<S: ToString>(mut self, note: S) -> Self {
+        self.notes.push((note.to_string(), None));
+        self
+    }
+
+    pub fn note_at<S: ToString>(mut self, note: S, span: Span) -> Self {
+        self.notes.push((note.to_string(), Some(span)));
+        self
+    }
+
+    pub fn from_parse_error(err: &ParseError) -> Self {
+        let msg = match err.reason() {
+            SimpleReason::Custom(msg) => msg.clone(),
+            SimpleReason::Unclosed { delimiter, .. } => {
+                format!("unclosed delimiter {delimiter:?}")
+            }
+            SimpleReason::Unexpected => format!("unexpected {}", describe_char(err.found())),
+        };
+        let mut diagnostic = Self::new(msg).at(err.span());
+
+        if let SimpleReason::Unclosed { span, delimiter } = err.reason() {
+            diagnostic = diagnostic.note_at(format!("{delimiter:?} was opened here"), *span);
+        }
+
+        let mut expected = err
+            .expected()
+            .map(|c| describe_char(c.as_ref()))
+            .collect::<Vec<_>>();
+        expected.sort();
+        expected.dedup();
+        match expected.as_slice() {
+            [] => {}
+            [one] => diagnostic = diagnostic.note(format!("expected {one}")),
+            many => diagnostic = diagnostic.note(format!("expected one of {}", many.join(", "))),
+        }
+
+        diagnostic
+    }
+
+    /// Render the diagnostic for a file.
+    pub fn render(&self, file: &SourceFile) -> String {
+        let mut out = String::new();
+        writeln!(out, "error: {}", self.msg).unwrap();
+
+        let Some(span) = self.span else {
+            self.render_notes(&mut out, "", file);
+            return out;
+        };
+
+        let (start, end) = file.positions(span);
+        let text = file.line(start.line).unwrap_or("");
+        let line_nr = (start.line + 1).to_string();
+        let gutter = " ".repeat(line_nr.len());
+
+        writeln!(out, "{gutter}--> {}:{start}", file.name()).unwrap();
+        writeln!(out, "{gutter} |").unwrap();
+        writeln!(out, "{line_nr} | {text}").unwrap();
+
+        // Tabs are kept so the underline lines up with the source line. Spans
+        // covering multiple lines are only underlined on their first line.
+        let indent = text[..start.byte_col]
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect::<String>();
+        let end_col = if end.line == start.line {
+            end.col
+        } else {
+            text.chars().count()
+        };
+        let width = end_col.saturating_sub(start.col).max(1);
+        writeln!(out, "{gutter} | {indent}{}", "^".repeat(width)).unwrap();
+
+        self.render_notes(&mut out, &gutter, file);
+        out
+    }
+
+    fn render_notes(&self, out: &mut String, gutter: &str, file: &SourceFile) {
+        for (note, span) in &self.notes {
+            match span {
+                Some(span) => {
+                    let (start, _) = file.positions(*span);
+                    writeln!(out, "{gutter} = {note} ({}:{start})", file.name()).unwrap();
+                }
+                None => writeln!(out, "{gutter} = {note}").unwrap(),
+            }
+        }
+    }
+}
@@ -0,0 +1,325 @@
+//! Evaluate fully desugared programs.
+//!
+//! A fully desugared program consists only of literals. Before evaluation, it
+//! is quoted, meaning that it is turned into a [`Value`] (see [`quote`]). From
+//! then on, code is just data that happens to be evaluated.
+//!
+//! # Rules
+//!
+//! Values other than tables evaluate to themselves. Tables are evaluated
+//! depending on their keys:
+//!
+//! - `'{ quote: a }` evaluates to `a` without evaluating `a`.
+//! - `'{ raw: '{ .. } }` evaluates to a new table with the same keys as the
+//!   inner table. Its values are the evaluated values of the inner table. It
+//!   has the same origin as the inner table, so builtins can report errors
+//!   about it.
+//! - `'{ call: a, arg: b }` evaluates `a` and `b` and then calls `a` with `b`
+//!   as its argument. If `arg` is missing, it defaults to `nil`.
+//! - Any other table is a block. Its positional elements are evaluated in
+//!   order, followed by its named elements. If the block only has positional
+//!   elements, it evaluates to the value of its last element (or `nil` if it is
+//!   empty). Otherwise, it evaluates to a new table containing the values of its
+//!   named elements. Each block is evaluated in a new scope.
+//!
+//! # Scopes
+//!
+//! Scopes are ordinary tables mapping variable names to values, and `'scope()`
+//! returns the current one. Every scope except for the global scope has a
+//! parent scope, and its metatable redirects both `__index` and `__newindex`
+//! to it (see [`meta`]). This means that
+//!
+//! - reading a variable (`'get`) looks for it in the current scope, then in
+//!   its parent and so on,
+//! - assigning to a variable (`'set`) assigns to it in the innermost scope that
+//!   defines it, or in the global scope if no scope defines it, and
+//! - defining a local variable (`'setraw`) always assigns to the current scope,
+//!   shadowing variables of the same name in outer scopes.
+//!
+//! Tables can have metatables that customize some builtins and calls, see
+//! [`meta`].
+//!
+//! # Calls
+//!
+//! Builtins are called with their argument directly. Tables of the form
+//! `{ body, scope: s }` where `s` is a table are closures, as created by
+//! `function` definitions, unless they have other entries or a `__call` hook.
+//! Calling a closure evaluates `body` in a new scope whose parent is `s`, with
+//! `'arg()` returning the argument. All other tables are called through the
+//! `__call` hook of their metatable.
+//!
+//! Calls to closures can only be nested so deeply. Beyond that, evaluation
+//! fails with a stack overflow error. Evaluation recurses on the native stack,
+//! so it needs to happen on a thread with a stack of at least [`STACK_SIZE`].
+
+mod builtin;
+mod meta;
+mod quote;
+
+use std::{fmt, result};
+
+use crate::ast::Program;
+use crate::diagnostic::Diagnostic;
+use crate::gc::Heap;
+use crate::span::Span;
+use crate::table::{Key, Table};
+use crate::value::Value;
+
+#[derive(Debug)]
+pub struct Error {
+    pub msg: String,
+    /// Span of the innermost code that produced the error, if known.
+    pub span: Option<Span>,
+}
+
+impl Error {
+    pub fn new<S: ToString>(msg: S) -> Self {
+        Self {
+            msg: msg.to_string(),
+            span: None,
+        }
+    }
+
+    /// Attach a span to the error unless it already has a more precise one.
+    fn at(mut self, span: Span) -> Self {
+        if self.span.is_none() {
+            self.span = Some(span);
+        }
+        self
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.span {
+            Some(span) => write!(f, "{} (at {span:?})", self.msg),
+            None => write!(f, "{}", self.msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<Error> for Diagnostic {
+    fn from(err: Error) -> Self {
+        let diagnostic = Self::new(err.msg);
+        match err.span {
+            Some(span) => diagnostic.at(span),
+            None => diagnostic,
+        }
+    }
+}
+
+pub type Result<T> = result::Result<T, Error>;
+
+/// How many calls to closures may be active at once. Recursion is the only way
+/// to loop, so this is generous.
+const MAX_CALLS: usize = 20_000;
+
+/// The native stack size needed to evaluate [`MAX_CALLS`] nested calls, with
+/// plenty of room to spare even in debug builds.
+pub const STACK_SIZE: usize = 512 * 1024 * 1024;
+
+struct Frame {
+    scope: Table,
+    arg: Value,
+}
+
+pub struct Runtime {
+    heap: Heap,
+    frames: Vec<Frame>,
+    /// Number of closure calls in progress.
+    calls: usize,
+}
+
+impl Default for Runtime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Runtime {
+    pub fn new() -> Self {
+        let mut runtime = Self {
+            heap: Heap::new(),
+            frames: vec![],
+            calls: 0,
+        };
+        let scope = runtime.new_table();
+        runtime.frames.push(Frame {
+            scope,
+            arg: Value::Nil,
+        });
+        runtime
+    }
+
+    pub fn new_table(&mut self) -> Table {
+        self.heap.new_table()
+    }
+
+    pub fn heap(&mut self) -> &mut Heap {
+        &mut self.heap
+    }
+
+    fn frame(&self) -> &Frame {
+        self.frames.last().expect("runtime has no frame")
+    }
+
+    /// Create a new scope whose variables fall back to those of `parent`.
+    fn new_scope(&mut self, parent: Table) -> Table {
+        let meta = self.new_table();
+        meta.insert("__index".into(), parent.clone().into());
+        meta.insert("__newindex".into(), parent.into());
+        let scope = self.new_table();
+        scope.set_meta(Some(meta));
+        scope
+    }
+
+    /// Evaluate `f` in a new frame and remove the frame again, even if `f`
+    /// fails.
+    fn with_frame<T>(&mut self, frame: Frame, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        self.frames.push(frame);
+        let result = f(self);
+        self.frames.pop();
+        result
+    }
+
+    /// Quote and evaluate a fully desugared program.
+    pub fn run(&mut self, program: Program) -> Result<Value> {
+        let code = self.quote_program(program)?;
+        self.eval(code)
+    }
+
+    pub fn eval(&mut self, code: Value) -> Result<Value> {
+        match code {
+            Value::Table(table) => {
+                let span = table.origin();
+                let result = self.eval_table(table);
+                match span {
+                    Some(span) => result.map_err(|e| e.at(span)),
+                    None => result,
+                }
+            }
+            value => Ok(value),
+        }
+    }
+
+    fn eval_table(&mut self, table: Table) -> Result<Value> {
+        if let Some(quoted) = table.get(&"quote".into()) {
+            return Ok(quoted);
+        }
+
+        if let Some(raw) = table.get(&"raw".into()) {
+            let Value::Table(raw) = raw else {
+                return Err(Error::new(format!(
+                    "raw must be a table, not {}",
+                    raw.type_name()
+                )));
+            };
+            let result = self.new_table();
+            if let Some(span) = raw.origin() {
+                result.set_origin(span);
+            }
+            for (key, code) in raw.iter() {
+                let value = self.eval(code)?;
+                result.insert(key, value);
+            }
+            return Ok(result.into());
+        }
+
+        if let Some(call) = table.get(&"call".into()) {
+            let arg = table.get(&"arg".into()).unwrap_or(Value::Nil);
+            let function = self.eval(call)?;
+            let arg = self.eval(arg)?;
+            return self.call(function, arg);
+        }
+
+        let frame = Frame {
+            scope: self.new_scope(self.frame().scope.clone()),
+            arg: self.frame().arg.clone(),
+        };
+        self.with_frame(frame, |this| this.eval_block(table))
+    }
+
+    fn eval_block(&mut self, table: Table) -> Result<Value> {
+        let mut last = Value::Nil;
+        let mut len = 0;
+        let mut named = vec![];
+        for (key, code) in table.iter() {
+            let value = self.eval(code)?;
+            if let Some(i) = key.as_int() {
+                last = value;
+                len = len.max(i + 1);
+            } else {
+                named.push((key, value));
+            }
+        }
+
+        // Positional `nil`s aren't stored in the table, so a block ending in
+        // one would otherwise evaluate to the element before it.
+        if table.literal_len().is_some_and(|n| n as i64 > len) {
+            last = Value::Nil;
+        }
+
+        if named.is_empty() {
+            Ok(last)
+        } else {
+            let result = self.new_table();
+            for (key, value) in named {
+                result.insert(key, value);
+            }
+            Ok(result.into())
+        }
+    }
+
+    pub fn call(&mut self, function: Value, arg: Value) -> Result<Value> {
+        match function {
+            Value::Builtin(builtin) => self.call_builtin(builtin, arg),
+            Value::Table(table) => match closure(&table) {
+                Some((body, scope)) => {
+                    if self.calls >= MAX_CALLS {
+                        return Err(Error::new("stack overflow"));
+                    }
+                    let frame = Frame {
+                        scope: self.new_scope(scope),
+                        arg,
+                    };
+                    self.calls += 1;
+                    let result = self.with_frame(frame, |this| this.eval(body));
+                    self.calls -= 1;
+                    result
+                }
+                None => self.call_table(table, arg),
+            },
+            value => Err(Error::new(format!("cannot call {}", value.type_name()))),
+        }
+    }
+}
+
+/// Split a closure into its body and the scope it was defined in.
+///
+/// The body is missing if it is `nil`, so a closure has a `scope` entry and at
+/// most one other entry, the body at key `0`. A `__call` hook takes precedence
+/// over treating the table as a closure.
+fn closure(table: &Table) -> Option<(Value, Table)> {
+    if let Some(meta) = table.meta() {
+        if meta.contains(&"__call".into()) {
+            return None;
+        }
+    }
+    let Value::Table(scope) = table.get(&"scope".into())? else {
+        return None;
+    };
+    let body = table.get(&0.into());
+    if table.entry_count() != 1 + usize::from(body.is_some()) {
+        return None;
+    }
+    Some((body.unwrap_or(Value::Nil), scope))
+}
+
+fn key(value: Value) -> Result<Key> {
+    Key::try_from(value).map_err(|v| match v {
+        Value::Float(_) => Error::new("NaN can't be used as a key"),
+        v => Error::new(format!("{} can't be used as a key", v.type_name())),
+    })
+}
@@ -0,0 +1,362 @@
+//! Semantics of the values in [`crate::builtin`].
+
+use std::cmp::Ordering;
+
+use crate::builtin::Builtin;
+use crate::table::{Key, Table};
+use crate::value::Value;
+
+use super::{key, Error, Result, Runtime};
+
+fn expect_table(builtin: Builtin, value: Value) -> Result<Table> {
+    match value {
+        Value::Table(table) => Ok(table),
+        value => Err(Error::new(format!(
+            "{builtin:?} expected a table, got {}",
+            value.type_name()
+        ))),
+    }
+}
+
+/// An int or float operand of an arithmetic builtin.
+#[derive(Clone, Copy)]
+enum Num {
+    Int(i64),
+    Float(f64),
+}
+
+impl Num {
+    fn to_float(self) -> f64 {
+        match self {
+            Self::Int(i) => i as f64,
+            Self::Float(f) => f,
+        }
+    }
+}
+
+fn expect_num(builtin: Builtin, value: Value) -> Result<Num> {
+    match value {
+        Value::Int(i) => Ok(Num::Int(i)),
+        Value::Float(f) => Ok(Num::Float(f)),
+        value => Err(Error::new(format!(
+            "{builtin:?} expected a number, got {}",
+            value.type_name()
+        ))),
+    }
+}
+
+fn args<const N: usize>(builtin: Builtin, arg: Value) -> Result<[Value; N]> {
+    let mut elems = expect_table(builtin, arg)?.positional();
+    Ok(std::array::from_fn(|_| elems.next().unwrap_or(Value::Nil)))
+}
+
+fn overflow(builtin: Builtin) -> Error {
+    Error::new(format!("int overflow in {builtin:?}"))
+}
+
+/// Int division, rounding towards negative infinity.
+fn div_floor(a: i64, b: i64) -> Option<i64> {
+    let q = a.checked_div(b)?;
+    if (a % b != 0) && ((a < 0) != (b < 0)) {
+        Some(q - 1)
+    } else {
+        Some(q)
+    }
+}
+
+/// Remainder of [`div_floor`], which has the same sign as the divisor.
+fn mod_floor(a: i64, b: i64) -> Option<i64> {
+    let r = a.checked_rem(b)?;
+    if (r != 0) && ((r < 0) != (b < 0)) {
+        Some(r + b)
+    } else {
+        Some(r)
+    }
+}
+
+/// Floored float remainder, which has the same sign as the divisor.
+fn mod_floor_float(a: f64, b: f64) -> f64 {
+    a - (a / b).floor() * b
+}
+
+/// Compare two values, returning `None` if either of them is `NaN`.
+fn compare(builtin: Builtin, a: &Value, b: &Value) -> Result<Option<Ordering>> {
+    match (a, b) {
+        (Value::Int(a), Value::Int(b)) => Ok(Some(a.cmp(b))),
+        (Value::Int(a), Value::Float(b)) => Ok((*a as f64).partial_cmp(b)),
+        (Value::Float(a), Value::Int(b)) => Ok(a.partial_cmp(&(*b as f64))),
+        (Value::Float(a), Value::Float(b)) => Ok(a.partial_cmp(b)),
+        (Value::String(a), Value::String(b)) => Ok(Some(a.cmp(b))),
+        (a, b) => Err(Error::new(format!(
+            "{builtin:?} can't compare {} with {}",
+            a.type_name(),
+            b.type_name()
+        ))),
+    }
+}
+
+/// Apply an arithmetic operation.
+///
+/// If both operands are ints, the int operation is used and the result is an
+/// int. Otherwise, both operands are converted to floats.
+fn arith(
+    builtin: Builtin,
+    arg: Value,
+    int_op: fn(i64, i64) -> Option<i64>,
+    float_op: fn(f64, f64) -> f64,
+) -> Result<Value> {
+    let [a, b] = args(builtin, arg)?;
+    match (expect_num(builtin, a)?, expect_num(builtin, b)?) {
+        (Num::Int(_), Num::Int(0)) if matches!(builtin, Builtin::Div | Builtin::Mod) => {
+            Err(Error::new(format!("int division by zero in {builtin:?}")))
+        }
+        (Num::Int(a), Num::Int(b)) => int_op(a, b)
+            .map(Value::Int)
+            .ok_or_else(|| overflow(builtin)),
+        (a, b) => Ok(Value::Float(float_op(a.to_float(), b.to_float()))),
+    }
+}
+
+fn cmp(builtin: Builtin, arg: Value, f: fn(Ordering) -> bool) -> Result<Value> {
+    let [a, b] = args(builtin, arg)?;
+    Ok(Value::Bool(
+        compare(builtin, &a, &b)?.map(f).unwrap_or(false),
+    ))
+}
+
+/// Ensure that a key required by a pattern is present.
+fn required(pattern_key: Key, elem: Value) -> Result<Value> {
+    match elem {
+        Value::Nil => Err(Error::new(format!(
+            "{:?}: value has no key {} required by the pattern",
+            Builtin::Destructure,
+            Value::from(pattern_key)
+        ))),
+        elem => Ok(elem),
+    }
+}
+
+impl Runtime {
+    pub(super) fn call_builtin(&mut self, builtin: Builtin, arg: Value) -> Result<Value> {
+        if let Some(result) = self.call_operator_hook(builtin, &arg) {
+            return result;
+        }
+
+        match builtin {
+            Builtin::Get => {
+                let [table, index] = args(builtin, arg)?;
+                let table = expect_table(builtin, table)?;
+                self.get(table, key(index)?)
+            }
+            Builtin::GetRaw => {
+                let [table, index] = args(builtin, arg)?;
+                let table = expect_table(builtin, table)?;
+                Ok(table.get(&key(index)?).unwrap_or(Value::Nil))
+            }
+
+            Builtin::Set => {
+                let [table, index, value] = args(builtin, arg)?;
+                let table = expect_table(builtin, table)?;
+                self.set(table.clone(), key(index)?, value)?;
+                Ok(table.into())
+            }
+            Builtin::SetRaw => {
+                let [table, index, value] = args(builtin, arg)?;
+                let table = expect_table(builtin, table)?;
+                table.insert(key(index)?, value);
+                Ok(table.into())
+            }
+
+            // Only tables can have metatables.
+            Builtin::GetMeta => Ok(match arg {
+                Value::Table(table) => table.meta().map_or(Value::Nil, Value::from),
+                _ => Value::Nil,
+            }),
+            Builtin::SetMeta => {
+                let [table, meta] = args(builtin, arg)?;
+                let table = expect_table(builtin, table)?;
+                let meta = match meta {
+                    Value::Nil => None,
+                    meta => Some(expect_table(builtin, meta)?),
+                };
+                table.set_meta(meta);
+                Ok(table.into())
+            }
+
+            Builtin::Scope => Ok(self.frame().scope.clone().into()),
+            Builtin::Arg => Ok(self.frame().arg.clone()),
+            Builtin::Destructure => self.destructure(arg),
+
+            Builtin::Neg => match expect_num(builtin, arg)? {
+                Num::Int(i) => i
+                    .checked_neg()
+                    .map(Value::Int)
+                    .ok_or_else(|| overflow(builtin)),
+                Num::Float(f) => Ok(Value::Float(-f)),
+            },
+            Builtin::Not => Ok(Value::Bool(!arg.truthy())),
+
+            Builtin::Mul => arith(builtin, arg, i64::checked_mul, |a, b| a * b),
+            Builtin::Div => arith(builtin, arg, div_floor, |a, b| a / b),
+            Builtin::Mod => arith(builtin, arg, mod_floor, mod_floor_float),
+            Builtin::Add => arith(builtin, arg, i64::checked_add, |a, b| a + b),
+            Builtin::Sub => arith(builtin, arg, i64::checked_sub, |a, b| a - b),
+
+            Builtin::Eq => {
+                let [a, b] = args(builtin, arg)?;
+                Ok(Value::Bool(a == b))
+            }
+            Builtin::Ne => {
+                let [a, b] = args(builtin, arg)?;
+                Ok(Value::Bool(a != b))
+            }
+            Builtin::Gt => cmp(builtin, arg, Ordering::is_gt),
+            Builtin::Ge => cmp(builtin, arg, Ordering::is_ge),
+            Builtin::Lt => cmp(builtin, arg, Ordering::is_lt),
+            Builtin::Le => cmp(builtin, arg, Ordering::is_le),
+
+            // Both operands have already been evaluated at this point, so
+            // these do not short-circuit.
+            Builtin::And => {
+                let [a, b] = args(builtin, arg)?;
+                Ok(if a.truthy() { b } else { a })
+            }
+            Builtin::Or => {
+                let [a, b] = args(builtin, arg)?;
+                Ok(if a.truthy() { a } else { b })
+            }
+        }
+    }
+
+    /// Implementation of `'destructure{ scope, pattern, value, rest: r, local: l }`.
+    ///
+    /// The pattern maps keys of `value` to targets, which are either the name
+    /// of a variable or a table with the following keys:
+    ///
+    /// - `name`: the name of a variable, or
+    /// - `pattern` and optionally `rest`: a nested pattern that the element is
+    ///   destructured with, like `pattern` and `r` are for `value`,
+    /// - `default` (optional): a function that is called to produce the element
+    ///   if the key is missing from `value`.
+    ///
+    /// Keys of the pattern without default must be present in `value`. If `r`
+    /// is given, the entries of `value` whose keys are not part of the pattern
+    /// are collected in a new table that is assigned to the variable named `r`.
+    /// Otherwise, they are ignored.
+    ///
+    /// If `l` is truthy, variables are defined in `scope` like `'setraw` would.
+    /// Otherwise, they are assigned like `'set` would.
+    fn destructure(&mut self, arg: Value) -> Result<Value> {
+        let builtin = Builtin::Destructure;
+        let arg = expect_table(builtin, arg)?;
+        let local = arg.get(&"local".into()).is_some_and(|l| l.truthy());
+        let rest = arg.get(&"rest".into());
+        let [scope, pattern, value] = args(builtin, arg.into())?;
+        let scope = expect_table(builtin, scope)?;
+        let pattern = expect_table(builtin, pattern)?;
+        self.destructure_pattern(&scope, local, &pattern, rest, value)?;
+        Ok(Value::Nil)
+    }
+
+    fn bind(&mut self, scope: &Table, local: bool, name: Value, value: Value) -> Result<()> {
+        if local {
+            scope.insert(key(name)?, value);
+            Ok(())
+        } else {
+            self.set(scope.clone(), key(name)?, value)
+        }
+    }
+
+    /// Errors about the pattern or the value point at the innermost pattern
+    /// involved.
+    fn destructure_pattern(
+        &mut self,
+        scope: &Table,
+        local: bool,
+        pattern: &Table,
+        rest: Option<Value>,
+        value: Value,
+    ) -> Result<()> {
+        let result = self.destructure_pattern_elems(scope, local, pattern, rest, value);
+        match pattern.origin() {
+            Some(span) => result.map_err(|e| e.at(span)),
+            None => result,
+        }
+    }
+
+    fn destructure_pattern_elems(
+        &mut self,
+        scope: &Table,
+        local: bool,
+        pattern: &Table,
+        rest: Option<Value>,
+        value: Value,
+    ) -> Result<()> {
+        let builtin = Builtin::Destructure;
+        let value = match value {
+            Value::Table(value) => value,
+            value => {
+                return Err(Error::new(format!(
+                    "{builtin:?} can't destructure {}",
+                    value.type_name()
+                )))
+            }
+        };
+
+        for (pattern_key, target) in pattern.iter() {
+            let elem = self.get(value.clone(), pattern_key.clone())?;
+            match target {
+                Value::Table(target) => {
+                    let result = self.destructure_target(scope, local, &target, pattern_key, elem);
+                    if let Some(span) = target.origin() {
+                        result.map_err(|e| e.at(span))?;
+                    } else {
+                        result?;
+                    }
+                }
+                name => {
+                    let elem = required(pattern_key, elem)?;
+                    self.bind(scope, local, name, elem)?;
+                }
+            }
+        }
+
+        if let Some(rest) = rest {
+            let rest_table = self.new_table();
+            for (key, elem) in value.iter() {
+                if !pattern.contains(&key) {
+                    rest_table.insert(key, elem);
+                }
+            }
+            self.bind(scope, local, rest, rest_table.into())?;
+        }
+
+        Ok(())
+    }
+
+    fn destructure_target(
+        &mut self,
+        scope: &Table,
+        local: bool,
+        target: &Table,
+        pattern_key: Key,
+        elem: Value,
+    ) -> Result<()> {
+        let elem = match (elem, target.get(&"default".into())) {
+            (Value::Nil, Some(default)) => self.call(default, Value::Nil)?,
+            (elem, _) => required(pattern_key, elem)?,
+        };
+
+        if let Some(name) = target.get(&"name".into()) {
+            self.bind(scope, local, name, elem)
+        } else if let Some(Value::Table(pattern)) = target.get(&"pattern".into()) {
+            let rest = target.get(&"rest".into());
+            self.destructure_pattern(scope, local, &pattern, rest, elem)
+        } else {
+            Err(Error::new(format!(
+                "{:?} target needs a name or a pattern",
+                Builtin::Destructure
+            )))
+        }
+    }
+}
@@ -0,0 +1,177 @@
+//! Metatables and the hooks they provide.
+//!
+//! Any table can have a metatable, which is set with `'setmeta{t, m}` and read
+//! with `'getmeta(t)`. Some builtins look for hooks in the metatables of their
+//! operands before falling back to their default behaviour:
+//!
+//! - `'get{t, k}`: If `t` has no entry for `k` and its metatable has an
+//!   `__index` entry, that entry is used instead. If it is a table, the lookup
+//!   is repeated on that table. Otherwise, it is called with `{t, k}`.
+//! - `'set{t, k, v}`: If `t` has no entry for `k` and its metatable has a
+//!   `__newindex` entry, that entry is used instead. If it is a table, the
+//!   assignment is repeated on that table. Otherwise, it is called with
+//!   `{t, k, v}`.
+//! - Calling a table `t` with the argument `a` calls the `__call` entry of its
+//!   metatable with `{t, a}`.
+//! - `'neg`, `'mul`, `'div`, `'mod`, `'add` and `'sub` call the `__neg`,
+//!   `__mul`, `__div`, `__mod`, `__add` or `__sub` entry of their first operand
+//!   that is a table and has such an entry. The hook receives the same argument
+//!   the builtin would have received.
+//!
+//! `'getraw` and `'setraw` never consult metatables.
+
+use crate::builtin::Builtin;
+use crate::table::{Key, Table};
+use crate::value::Value;
+
+use super::{Error, Result, Runtime};
+
+/// Look up a hook in the table's metatable.
+fn hook(table: &Table, name: &str) -> Option<Value> {
+    table.meta()?.get(&name.into())
+}
+
+/// Name of the hook that overrides an arithmetic builtin.
+fn operator_hook_name(builtin: Builtin) -> Option<&'static str> {
+    match builtin {
+        Builtin::Neg => Some("__neg"),
+        Builtin::Mul => Some("__mul"),
+        Builtin::Div => Some("__div"),
+        Builtin::Mod => Some("__mod"),
+        Builtin::Add => Some("__add"),
+        Builtin::Sub => Some("__sub"),
+        _ => None,
+    }
+}
+
+/// Detects cycles in a chain of tables without allocating, using Brent's
+/// algorithm: the table at every power of two steps is remembered and compared
+/// with all following tables.
+struct CycleCheck {
+    saved: Table,
+    power: usize,
+    steps: usize,
+}
+
+impl CycleCheck {
+    fn new(start: &Table) -> Self {
+        Self {
+            saved: start.clone(),
+            power: 1,
+            steps: 0,
+        }
+    }
+
+    /// Advance the chain to `next`. Returns whether the chain has run into a
+    /// cycle.
+    fn step(&mut self, next: &Table) -> bool {
+        if *next == self.saved {
+            return true;
+        }
+        self.steps += 1;
+        if self.steps == self.power {
+            self.saved = next.clone();
+            self.power *= 2;
+            self.steps = 0;
+        }
+        false
+    }
+}
+
+impl Runtime {
+    /// Create a table containing the values as positional elements.
+    fn tuple<const N: usize>(&mut self, values: [Value; N]) -> Value {
+        let table = self.new_table();
+        for (i, value) in values.into_iter().enumerate() {
+            table.insert(Key::from(i as i64), value);
+        }
+        table.into()
+    }
+
+    /// Look up a key in a table, respecting `__index`.
+    ///
+    /// Chains of `__index` tables may be arbitrarily long, which scopes rely
+    /// on, but must not contain cycles.
+    pub(super) fn get(&mut self, mut table: Table, key: Key) -> Result<Value> {
+        let mut cycle = CycleCheck::new(&table);
+        loop {
+            if let Some(value) = table.get(&key) {
+                return Ok(value);
+            }
+            match hook(&table, "__index") {
+                None => return Ok(Value::Nil),
+                Some(Value::Table(next)) => {
+                    if cycle.step(&next) {
+                        return Err(Error::new("__index chain contains a cycle"));
+                    }
+                    table = next;
+                }
+                Some(hook) => {
+                    let arg = self.tuple([table.into(), key.into()]);
+                    return self.call(hook, arg);
+                }
+            }
+        }
+    }
+
+    /// Assign to a key in a table, respecting `__newindex`.
+    ///
+    /// Like in [`Self::get`], chains of `__newindex` tables must not contain
+    /// cycles.
+    pub(super) fn set(&mut self, mut table: Table, key: Key, value: Value) -> Result<()> {
+        let mut cycle = CycleCheck::new(&table);
+        loop {
+            if table.contains(&key) {
+                table.insert(key, value);
+                return Ok(());
+            }
+            match hook(&table, "__newindex") {
+                None => {
+                    table.insert(key, value);
+                    return Ok(());
+                }
+                Some(Value::Table(next)) => {
+                    if cycle.step(&next) {
+                        return Err(Error::new("__newindex chain contains a cycle"));
+                    }
+                    table = next;
+                }
+                Some(hook) => {
+                    let arg = self.tuple([table.into(), key.into(), value]);
+                    return self.call(hook, arg).map(|_| ());
+                }
+            }
+        }
+    }
+
+    /// Call a table through its `__call` hook.
+    pub(super) fn call_table(&mut self, table: Table, arg: Value) -> Result<Value> {
+        match hook(&table, "__call") {
+            Some(hook) => {
+                let arg = self.tuple([table.into(), arg]);
+                self.call(hook, arg)
+            }
+            None => Err(Error::new("cannot call table without __call hook")),
+        }
+    }
+
+    /// Call the hook overriding an arithmetic builtin, if any of its operands
+    /// has one.
+    pub(super) fn call_operator_hook(
+        &mut self,
+        builtin: Builtin,
+        arg: &Value,
+    ) -> Option<Result<Value>> {
+        let name = operator_hook_name(builtin)?;
+        let operands = match (builtin, arg) {
+            (Builtin::Neg, arg) => vec![arg.clone()],
+            (_, Value::Table(arg)) => arg.positional().take(2).collect(),
+            _ => vec![],
+        };
+        let hook = operands.iter().find_map(|operand| match operand {
+            Value::Table(table) => hook(table, name),
+            _ => None,
+        })?;
+        Some(self.call(hook, arg.clone()))
+    }
+}
@@ -0,0 +1,77 @@
+//! Turn fully desugared programs into values.
+
+use crate::ast::{Expr, Lit, Program, StringLit, StringLitElem, TableLit, TableLitElem};
+use crate::span::HasSpan;
+use crate::value::Value;
+
+use super::{Error, Result, Runtime};
+
+fn string_lit_value(lit: StringLit) -> String {
+    let mut result = String::new();
+    for elem in lit.elems {
+        match elem {
+            StringLitElem::Plain(str) => result.push_str(&str),
+            StringLitElem::Unicode(char) => result.push(char),
+            StringLitElem::Backslash => result.push('\\'),
+            StringLitElem::DoubleQuote => result.push('"'),
+            StringLitElem::Tab => result.push('\t'),
+            StringLitElem::CarriageReturn => result.push('\r'),
+            StringLitElem::Newline => result.push('\n'),
+        }
+    }
+    result
+}
+
+impl Runtime {
+    pub fn quote_program(&mut self, program: Program) -> Result<Value> {
+        match program {
+            Program::Expr { expr, .. } => self.quote_expr(*expr),
+            Program::Module { span, .. } => {
+                Err(Error::new("program is not fully desugared").at(span))
+            }
+        }
+    }
+
+    fn quote_expr(&mut self, expr: Expr) -> Result<Value> {
+        match expr {
+            Expr::Lit(lit) => self.quote_lit(lit),
+            Expr::Error(span) => Err(Error::new("program contains a syntax error").at(span)),
+            expr => Err(Error::new("program is not fully desugared").at(expr.span())),
+        }
+    }
+
+    pub fn quote_lit(&mut self, lit: Lit) -> Result<Value> {
+        Ok(match lit {
+            Lit::Nil(_) => Value::Nil,
+            Lit::Bool(b, _) => Value::Bool(b),
+            Lit::Builtin(b, _) => Value::Builtin(b),
+            Lit::Num(n) => Value::Int(n.value),
+            Lit::Float(n) => Value::Float(n.value),
+            Lit::String(s) => string_lit_value(s).into(),
+            Lit::Table(t) => self.quote_table_lit(t)?,
+        })
+    }
+
+    fn quote_table_lit(&mut self, lit: TableLit) -> Result<Value> {
+        let table = self.new_table();
+        table.set_origin(lit.span());
+
+        let mut index: i64 = 0;
+        for (_, elem, _) in lit.0.elems {
+            match elem {
+                TableLitElem::Positional(expr) => {
+                    let value = self.quote_expr(*expr)?;
+                    table.insert(index.into(), value);
+                    index += 1;
+                }
+                TableLitElem::Named { name, value, .. } => {
+                    let value = self.quote_expr(*value)?;
+                    table.insert(name.name.as_str().into(), value);
+                }
+            }
+        }
+
+        table.set_literal_len(index as usize);
+        Ok(table.into())
+    }
+}
@@ -0,0 +1,122 @@
+//! Support for `tada fmt`.
+//!
+//! The formatter is configured by a `tadafmt.toml` file in the directory of the
+//! formatted file or one of its ancestors, usually at the root of a project. Only a
+//! small subset of TOML is supported: Each line is empty, a `#` comment or a
+//! `key = value` pair. The following keys are known:
+//!
+//! - `width`: The maximum line width the pretty printer aims for.
+
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+
+use anyhow::{anyhow, bail, Context};
+use similar::TextDiff;
+
+pub const CONFIG_FILE: &str = "tadafmt.toml";
+
+pub struct Config {
+    pub width: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self { width: 100 }
+    }
+}
+
+impl Config {
+    /// Find the config file in a directory or one of its ancestors and load
+    /// it. If there is none, the default config is used.
+    pub fn find(dir: &Path) -> anyhow::Result<Self> {
+        let dir = env::current_dir()?.join(dir);
+        for dir in dir.ancestors() {
+            let path = dir.join(CONFIG_FILE);
+            if path.is_file() {
+                return Self::load(&path);
+            }
+        }
+        Ok(Self::default())
+    }
+
+    /// Find the config file that applies to a file.
+    pub fn for_file(path: &Path) -> anyhow::Result<Self> {
+        Self::find(path.parent().unwrap_or(Path::new("")))
+    }
+
+    fn load(path: &Path) -> anyhow::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        Self::parse(&text).with_context(|| format!("invalid config file {}", path.display()))
+    }
+
+    fn parse(text: &str) -> anyhow::Result<Self> {
+        let mut config = Self::default();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| anyhow!("line {}: expected `key = value`", i + 1))?;
+            match key.trim() {
+                "width" => {
+                    config.width = value
+                        .trim()
+                        .parse()
+                        .with_context(|| format!("line {}: width must be a number", i + 1))?;
+                }
+                key => bail!("line {}: unknown key {key:?}", i + 1),
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// The width given on the command line, or otherwise the one from the config
+/// that applies to the file.
+pub fn width(flag: Option<usize>, path: &Path) -> anyhow::Result<usize> {
+    match flag {
+        Some(width) => Ok(width),
+        None => Ok(Config::for_file(path)?.width),
+    }
+}
+
+fn collect_dir(dir: &Path, files: &mut Vec<PathBuf>) -> anyhow::Result<()> {
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            collect_dir(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "tada") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Expand directories to the `.tada` files they contain, recursively. Files
+/// are kept as they are, regardless of their extension.
+pub fn tada_files(paths: &[PathBuf]) -> anyhow::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    for path in paths {
+        if path.is_dir() {
+            collect_dir(path, &mut files)?;
+        } else {
+            files.push(path.clone());
+        }
+    }
+    Ok(files)
+}
+
+/// Print a unified diff between a file's contents and their formatted version.
+pub fn show_diff(path: &Path, text: &str, formatted: &str) {
+    let name = path.display().to_string();
+    let diff = TextDiff::from_lines(text, formatted)
+        .unified_diff()
+        .header(&name, &format!("{name} (formatted)"))
+        .to_string();
+    print!("{diff}");
+}
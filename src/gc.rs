@@ -0,0 +1,147 @@
+//! Garbage collection for tables.
+//!
+//! Tables are reference counted (see [`Table`]), which frees most of them as
+//! soon as they become unreachable. Reference counting can't free cycles though,
+//! and in tada, cycles are everywhere: A function's `scope` field points to the
+//! scope that contains the function, for example.
+//!
+//! The [`Heap`] keeps track of all tables and periodically runs a mark-and-sweep
+//! collection over them to find cycles that are no longer reachable.
+//!
+//! # Roots
+//!
+//! Instead of requiring the evaluator to enumerate its roots, the collector
+//! infers them from reference counts: Any table that is referenced more often
+//! than other tables reference it must be referenced from outside the heap, for
+//! example by a frame of the evaluator, by a value on the Rust stack or by an
+//! embedding host. These tables are the roots. This means that collections are
+//! safe at any point, even in the middle of an evaluation.
+//!
+//! Unreachable tables are swept by clearing them, which breaks all cycles they
+//! are part of. Reference counting then takes care of freeing them.
+
+use std::collections::HashMap;
+
+use crate::table::{Table, WeakTable};
+
+/// Minimum number of allocations between two automatic collections.
+const MIN_THRESHOLD: usize = 1024;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Stats {
+    /// Tables allocated over the lifetime of the heap.
+    pub allocated: usize,
+    /// Tables freed by collections, not counting tables freed by reference
+    /// counting alone.
+    pub collected: usize,
+    /// Completed collections.
+    pub collections: usize,
+}
+
+pub struct Heap {
+    tables: Vec<WeakTable>,
+    /// Allocations since the last collection.
+    allocations: usize,
+    /// Number of allocations after which the next collection is triggered.
+    threshold: usize,
+    stats: Stats,
+}
+
+impl Heap {
+    pub fn new() -> Self {
+        Self {
+            tables: vec![],
+            allocations: 0,
+            threshold: MIN_THRESHOLD,
+            stats: Stats::default(),
+        }
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    /// Create a new table tracked by the collector.
+    ///
+    /// This may trigger a collection.
+    pub fn new_table(&mut self) -> Table {
+        self.allocations += 1;
+        if self.allocations >= self.threshold {
+            self.collect();
+        }
+
+        let table = Table::new();
+        self.tables.push(table.downgrade());
+        self.stats.allocated += 1;
+        table
+    }
+
+    /// Number of tables that are still alive.
+    ///
+    /// This includes unreachable tables that have not been collected yet.
+    pub fn live(&self) -> usize {
+        self.tables.iter().filter(|t| t.upgrade().is_some()).count()
+    }
+
+    /// Perform a full collection and return the number of tables it freed.
+    pub fn collect(&mut self) -> usize {
+        let tables = self
+            .tables
+            .drain(..)
+            .filter_map(|t| t.upgrade())
+            .collect::<Vec<_>>();
+        let index = tables
+            .iter()
+            .enumerate()
+            .map(|(i, t)| (t.clone(), i))
+            .collect::<HashMap<_, _>>();
+
+        // Count the references that come from outside the heap. The vector and
+        // the index each hold one reference to every table.
+        let mut external = tables.iter().map(|t| t.ref_count() - 2).collect::<Vec<_>>();
+        for table in &tables {
+            table.for_each_child(|child| {
+                if let Some(&i) = index.get(child) {
+                    external[i] -= 1;
+                }
+            });
+        }
+
+        // Mark everything reachable from the roots.
+        let mut marked = vec![false; tables.len()];
+        let mut stack = (0..tables.len())
+            .filter(|&i| external[i] > 0)
+            .collect::<Vec<_>>();
+        while let Some(i) = stack.pop() {
+            if marked[i] {
+                continue;
+            }
+            marked[i] = true;
+            tables[i].for_each_child(|child| {
+                if let Some(&j) = index.get(child) {
+                    if !marked[j] {
+                        stack.push(j);
+                    }
+                }
+            });
+        }
+        drop(index);
+
+        // Sweep
+        let mut collected = 0;
+        for (table, marked) in tables.into_iter().zip(marked) {
+            if marked {
+                self.tables.push(table.downgrade());
+            } else {
+                table.clear();
+                collected += 1;
+            }
+        }
+
+        self.allocations = 0;
+        self.threshold = MIN_THRESHOLD.max(2 * self.tables.len());
+        self.stats.collected += collected;
+        self.stats.collections += 1;
+        collected
+    }
+}
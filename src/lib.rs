@@ -0,0 +1,35 @@
+//! The tada language: parsing, desugaring, pretty printing and evaluation.
+
+#![deny(unsafe_code)]
+// Rustc lint groups
+#![warn(future_incompatible)]
+#![warn(rust_2018_idioms)]
+// Rustc lints
+#![warn(noop_method_call)]
+#![warn(single_use_lifetimes)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_crate_dependencies)]
+#![warn(unused_extern_crates)]
+#![warn(unused_import_braces)]
+#![warn(unused_lifetimes)]
+#![warn(unused_qualifications)]
+// Clippy lints
+#![warn(clippy::use_self)]
+
+// Only used by the binary
+use anyhow as _;
+use similar as _;
+use tempfile as _;
+
+pub mod ast;
+pub mod builtin;
+pub mod desugar;
+pub mod diagnostic;
+pub mod eval;
+mod gc;
+pub mod parser;
+pub mod pretty;
+pub mod source;
+pub mod span;
+pub mod table;
+pub mod value;
@@ -15,21 +15,23 @@
 #![warn(clippy::use_self)]
 
 use std::io::Write;
-use std::path::PathBuf;
-use std::{fs, process};
+use std::path::{Path, PathBuf};
+use std::{fs, panic, process, thread};
 
-use anyhow::anyhow;
 use chumsky::Parser as _;
 use clap::Parser;
+// Only used by the library
+use ::pretty as _;
+use indexmap as _;
 
-mod ast;
-mod builtin;
-mod desugar;
-mod parser;
-mod pretty;
-mod span;
-mod table;
-mod value;
+use tada::ast::{Program, SpanlessEq};
+use tada::desugar::{Kind, Provenance};
+use tada::diagnostic::Diagnostic;
+use tada::source::SourceFile;
+use tada::{eval, parser, pretty, span};
+
+mod fmt;
+mod repl;
 
 #[derive(Parser)]
 enum Command {
@@ -38,13 +40,57 @@ enum Command {
     },
     Pretty {
         file: PathBuf,
+        /// Maximum line width [default: from the config file, or 100]
+        #[arg(long)]
+        width: Option<usize>,
+    },
+    /// Format files in place.
+    Fmt {
+        /// Files or directories to format. Directories are searched for `.tada`
+        /// files recursively.
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        /// Don't write any files. Instead, show a diff and fail if a file is
+        /// not formatted.
+        #[arg(long)]
+        check: bool,
+        /// Maximum line width [default: from the config file, or 100]
+        #[arg(long)]
+        width: Option<usize>,
     },
+    /// Show how a program is desugared step by step.
+    ///
+    /// Every step applies one rule to the outermost, leftmost sugar. By
+    /// default, every step is shown as a diff with the previous step.
     Desugar {
         file: PathBuf,
         #[arg(long, short, default_value = "diff")]
         difftool: String,
         #[arg(long, short = 'a')]
         diffarg: Vec<String>,
+        /// Print every step to stdout instead of showing diffs, preceded by the
+        /// rule and where it was applied.
+        #[arg(long, conflicts_with = "final_only")]
+        steps: bool,
+        /// Print only the final program to stdout instead of showing diffs.
+        #[arg(long = "final")]
+        final_only: bool,
+        /// Stop after this many steps, even if the program is not fully
+        /// desugared yet.
+        #[arg(long)]
+        max_steps: Option<usize>,
+        /// Only desugar these kinds of constructs, separated by commas.
+        /// Other constructs are kept, but their children are desugared.
+        #[arg(long, value_delimiter = ',')]
+        only: Vec<Kind>,
+    },
+    /// Evaluate programs interactively.
+    Repl,
+    Run {
+        file: PathBuf,
+        /// Print garbage collector statistics after running.
+        #[arg(long)]
+        gc_stats: bool,
     },
 }
 
@@ -54,72 +100,250 @@ struct Args {
     command: Command,
 }
 
+/// Read a source file from disk.
+fn read(path: &Path) -> anyhow::Result<SourceFile> {
+    let text = fs::read_to_string(path)?;
+    Ok(SourceFile::new(path.display().to_string(), text))
+}
+
+/// Print diagnostics for a file to stderr.
+fn report(file: &SourceFile, diagnostics: impl IntoIterator<Item = Diagnostic>) {
+    for diagnostic in diagnostics {
+        eprint!("{}", diagnostic.render(file));
+    }
+}
+
+/// Print diagnostics for a file to stderr and exit with an error.
+fn fail(file: &SourceFile, diagnostics: impl IntoIterator<Item = Diagnostic>) -> ! {
+    report(file, diagnostics);
+    process::exit(1);
+}
+
+/// Turn a runtime error into a diagnostic that also names the sugar the
+/// failing code was desugared from.
+fn runtime_error(file: &SourceFile, provenance: &Provenance, err: eval::Error) -> Diagnostic {
+    let origin = err
+        .span
+        .and_then(|span| provenance.chain(span).first().copied());
+    let diagnostic = Diagnostic::from(err);
+    let Some((rule, span)) = origin else {
+        return diagnostic;
+    };
+
+    // Sugar spanning multiple lines is shortened to its first line
+    let text = &file.text()[file.byte_range(span)];
+    let text = match text.split_once('\n') {
+        Some((first, _)) => format!("{} ..", first.trim_end()),
+        None => text.to_string(),
+    };
+    diagnostic.note_at(format!("in {} `{text}`", rule.description()), span)
+}
+
+/// Parse a program, recovering from errors where possible.
+///
+/// Returns the (possibly partial) program and a diagnostic for every error,
+/// ordered by their position in the file. Recovery may report several errors
+/// at the same position, of which only the first and most specific is kept.
+fn parse_partial(file: &SourceFile) -> (Option<Program>, Vec<Diagnostic>) {
+    parse_partial_at(file.text(), 0)
+}
+
+/// Like [`parse_partial`], but for text starting at character `offset` of its
+/// file.
+fn parse_partial_at(text: &str, offset: usize) -> (Option<Program>, Vec<Diagnostic>) {
+    let stream = span::stream_from_str_at(text, offset);
+    let (program, mut errs) = parser::parser().parse_recovery(stream);
+    errs.sort_by_key(|err| err.span().range().start);
+    errs.dedup_by_key(|err| err.span().range().start);
+    let diagnostics = errs.iter().map(Diagnostic::from_parse_error).collect();
+    (program, diagnostics)
+}
+
+/// Parse a program, exiting with diagnostics if it can't be parsed.
+fn parse(file: &SourceFile) -> Program {
+    match parse_partial(file) {
+        (Some(program), diagnostics) if diagnostics.is_empty() => program,
+        (_, diagnostics) => fail(file, diagnostics),
+    }
+}
+
 /// Foo
 fn main() -> anyhow::Result<()> {
     let args = Args::parse();
+    // Evaluation recurses on the native stack, which needs to be large enough
+    // for the deepest recursion it allows.
+    thread::Builder::new()
+        .stack_size(eval::STACK_SIZE)
+        .spawn(move || run(args))?
+        .join()
+        .unwrap_or_else(|panic| panic::resume_unwind(panic))
+}
 
+fn run(args: Args) -> anyhow::Result<()> {
     match args.command {
         Command::Parse { file } => {
-            let content = fs::read_to_string(&file)?;
-            let stream = span::stream_from_str(&content);
-            match parser::parser().parse(stream) {
-                Ok(program) => println!("Successful parse: {program:#?}"),
-                Err(errs) => {
-                    println!("Parsing failed");
-                    for err in errs {
-                        println!("{err:?}");
-                    }
+            let file = read(&file)?;
+            let (program, diagnostics) = parse_partial(&file);
+            if let Some(program) = program {
+                if diagnostics.is_empty() {
+                    println!("Successful parse: {program:#?}");
+                } else {
+                    println!("Partial parse: {program:#?}");
                 }
             }
+            if !diagnostics.is_empty() {
+                fail(&file, diagnostics);
+            }
         }
 
-        Command::Pretty { file } => {
-            let content = fs::read_to_string(&file)?;
-            let stream = span::stream_from_str(&content);
-            let program = parser::parser()
-                .parse(stream)
-                .map_err(|e| anyhow!("{e:?}"))?;
+        Command::Pretty { file, width } => {
+            let width = fmt::width(width, &file)?;
+            let file = read(&file)?;
+            let program = parse(&file);
+            print!("{}", pretty::pretty_to_string(program, width));
+        }
 
-            print!("{}", pretty::pretty_to_string(program, 100));
+        Command::Fmt {
+            paths,
+            check,
+            width,
+        } => {
+            let mut ok = true;
+            for path in fmt::tada_files(&paths)? {
+                let width = fmt::width(width, &path)?;
+                let file = read(&path)?;
+                let program = match parse_partial(&file) {
+                    (Some(program), diagnostics) if diagnostics.is_empty() => program,
+                    (_, diagnostics) => {
+                        report(&file, diagnostics);
+                        ok = false;
+                        continue;
+                    }
+                };
+
+                let formatted = pretty::pretty_to_string(program.clone(), width);
+                if formatted == file.text() {
+                    continue;
+                }
+
+                // A bug in the pretty printer shouldn't destroy any code or
+                // comments.
+                let reparsed = parser::parser().parse(span::stream_from_str(&formatted));
+                if !reparsed.is_ok_and(|reparsed| reparsed.spanless_eq(&program))
+                    || pretty::comments(file.text()) != pretty::comments(&formatted)
+                {
+                    let msg = "formatting would change the meaning of this file";
+                    report(&file, [Diagnostic::new(msg).note("this is a bug in tada")]);
+                    ok = false;
+                    continue;
+                }
+
+                if check {
+                    fmt::show_diff(&path, file.text(), &formatted);
+                    ok = false;
+                } else {
+                    fs::write(&path, formatted)?;
+                }
+            }
+            if !ok {
+                process::exit(1);
+            }
         }
 
         Command::Desugar {
             file,
             difftool,
             diffarg,
+            steps,
+            final_only,
+            max_steps,
+            only,
         } => {
-            let content = fs::read_to_string(&file)?;
-            let stream = span::stream_from_str(&content);
-            let mut program = parser::parser()
-                .parse(stream)
-                .map_err(|e| anyhow!("{e:?}"))?;
+            let kinds = if only.is_empty() {
+                Kind::all()
+            } else {
+                only.into_iter().collect()
+            };
+            let width = fmt::Config::for_file(&file)?.width;
+            let file = read(&file)?;
+            let mut program = parse(&file);
+
+            if final_only && max_steps.is_none() {
+                let (program, _) = program.desugar_fully(&kinds);
+                print!("{}", pretty::pretty_to_string(program, width));
+                return Ok(());
+            }
 
             let mut builder = tempfile::Builder::new();
             builder.suffix(".tada");
+            let mut prev: Option<tempfile::NamedTempFile> = None;
 
-            let mut prev = builder.tempfile()?;
-            prev.write_all(pretty::pretty_to_string(program.clone(), 100).as_bytes())?;
-            prev.flush()?;
+            for n in 0.. {
+                let mut header = format!("# step {n}");
+                if n > 0 {
+                    if max_steps.is_some_and(|max| n > max) {
+                        break;
+                    }
+                    let (new_program, step) = program.desugar(&kinds);
+                    program = new_program;
+                    let Some(step) = step else {
+                        break;
+                    };
+                    let (start, end) = file.positions(step.span);
+                    header = format!("{header}: {} at {start}-{end}", step.rule);
+                }
+
+                if steps {
+                    println!("{header}");
+                    print!("{}", pretty::pretty_to_string(program.clone(), width));
+                } else if !final_only {
+                    let mut cur = builder.tempfile()?;
+                    cur.write_all(pretty::pretty_to_string(program.clone(), width).as_bytes())?;
+                    cur.flush()?;
 
-            loop {
-                let (new_program, desugared) = program.desugar();
-                program = new_program;
-                if !desugared {
-                    break;
+                    if let Some(prev) = prev {
+                        process::Command::new(&difftool)
+                            .args(&diffarg)
+                            .arg(prev.path())
+                            .arg(cur.path())
+                            .spawn()?
+                            .wait()?;
+                    }
+
+                    prev = Some(cur);
                 }
+            }
 
-                let mut cur = builder.tempfile()?;
-                cur.write_all(pretty::pretty_to_string(program.clone(), 100).as_bytes())?;
-                cur.flush()?;
+            if final_only {
+                print!("{}", pretty::pretty_to_string(program, width));
+            }
+        }
+
+        Command::Repl => repl::repl()?,
 
-                process::Command::new(&difftool)
-                    .args(&diffarg)
-                    .arg(prev.path())
-                    .arg(cur.path())
-                    .spawn()?
-                    .wait()?;
+        Command::Run { file, gc_stats } => {
+            let file = read(&file)?;
+            let (program, provenance) = parse(&file).desugar_fully(&Kind::all());
+
+            let mut runtime = eval::Runtime::new();
+            let result = runtime.run(program);
+
+            if gc_stats {
+                let heap = runtime.heap();
+                heap.collect();
+                let stats = heap.stats();
+                eprintln!(
+                    "gc: {} allocated, {} collected in {} collections, {} live",
+                    stats.allocated,
+                    stats.collected,
+                    stats.collections,
+                    heap.live()
+                );
+            }
 
-                prev = cur;
+            match result {
+                Ok(value) => println!("{value}"),
+                Err(err) => fail(&file, [runtime_error(&file, &provenance, err)]),
             }
         }
     }
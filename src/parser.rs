@@ -31,17 +31,17 @@ use chumsky::prelude::*;
 
 use crate::ast::Program;
 
-use self::basic::Error;
+pub use self::basic::Error;
 
 pub fn parser() -> impl Parser<char, Program, Error = Error> {
     let space = basic::space();
     let ident = basic::ident();
     let local = basic::local(space.clone());
-    let table_pattern = table_destr::table_pattern(space.clone(), ident.clone());
 
     let expr = recursive(|expr| {
         let expr = expr.boxed();
 
+        let table_pattern = table_destr::table_pattern(space.clone(), ident.clone(), expr.clone());
         let table_lit_elem = lit::table_lit_elem(space.clone(), ident.clone(), expr.clone());
         let lit = lit::lit(space.clone(), table_lit_elem.clone());
         let var = var::var(space.clone(), ident.clone(), local.clone(), expr.clone());
@@ -56,7 +56,79 @@ pub fn ident() -> EParser<Ident> {
 }
 
 pub fn local(space: EParser<Space>) -> EParser<Option<Space>> {
-    text::keyword("local").ignore_then(space).or_not().boxed()
+    keyword("local").ignore_then(space).or_not().boxed()
+}
+
+// Like `bounded_separated` below, these are utility functions, so the rules
+// specified in the `parser` module don't apply to them.
+
+/// Like [`text::keyword`], but failing with an error that says which
+/// identifier was found instead.
+pub fn keyword(name: &'static str) -> impl Parser<char, (), Error = Error> + Clone {
+    text::ident().try_map(move |ident: String, span| {
+        if ident == name {
+            Ok(())
+        } else {
+            let found = ident.chars().next();
+            Err(Error::expected_input_found(span, [], found))
+        }
+    })
+}
+
+/// Succeed without consuming any input if `parser` would succeed here, and fail
+/// at the current position otherwise.
+///
+/// Unlike with [`Parser::rewind`], the errors `parser` encountered along the
+/// way are forgotten. Chumsky would otherwise report them in place of errors
+/// that happen before their position, for example inside the input that was
+/// looked at. Failing is the only way to get rid of them, hence the detour.
+pub fn lookahead<O>(
+    parser: impl Parser<char, O, Error = Error> + Clone,
+) -> impl Parser<char, (), Error = Error> + Clone {
+    let next = any().or_not().rewind();
+    next.then(parser.or_not().rewind())
+        .try_map(|(next, out), span| match out {
+            Some(_) => Err(Error::custom(span, "lookahead succeeded")),
+            None => Ok(Some(next)),
+        })
+        .or_else(|_| Ok(None))
+        .try_map(|next, span| match next {
+            Some(found) => Err(Error::expected_input_found(span, [], found)),
+            None => Ok(()),
+        })
+}
+
+/// Recover from a parser whose input starts with `open` by skipping to the
+/// matching `close`, respecting nested `()`, `[]` and `{}`.
+pub fn recover_delimited<O>(
+    open: char,
+    close: char,
+    parser: impl Parser<char, O, Error = Error> + Clone,
+    error: fn(Span) -> O,
+) -> impl Parser<char, O, Error = Error> + Clone {
+    // The pair of `open` and `close` is ignored when it appears in `others`.
+    let others = [('(', ')'), ('[', ']'), ('{', '}')];
+    parser.recover_with(nested_delimiters(open, close, others, error))
+}
+
+/// Recover from an element of [`bounded_separated`] that fails to parse by
+/// skipping everything up to the next synchronization character.
+///
+/// The synchronization characters should be the separator and the closing
+/// delimiter. An element that is not directly followed by one of them (or the
+/// end of the input) is skipped as well. Input starting with a synchronization
+/// character is never skipped, so empty elements are still rejected normally.
+pub fn recover_elem<E, const N: usize>(
+    space: EParser<Space>,
+    sync: [char; N],
+    elem: impl Parser<char, E, Error = Error> + Clone,
+    error: fn(Span) -> E,
+) -> impl Parser<char, E, Error = Error> + Clone {
+    let followed = space.then(one_of(sync).to(()).or(end())).rewind();
+    none_of(sync).rewind().ignore_then(
+        elem.then_ignore(followed)
+            .recover_with(skip_until(sync, error)),
+    )
 }
 
 // This function is more of a utility function. Because of this and to keep the
@@ -66,37 +138,38 @@ pub fn bounded_separated<E: 'static>(
     space: impl Parser<char, Space, Error = Error> + Clone + 'static,
     start: impl Parser<char, (), Error = Error> + 'static,
     end: impl Parser<char, (), Error = Error> + 'static,
-    separator: impl Parser<char, (), Error = Error> + 'static,
+    separator: impl Parser<char, (), Error = Error> + Clone + 'static,
     elem: impl Parser<char, E, Error = Error> + Clone + 'static,
 ) -> EParser<BoundedSeparated<E>> {
+    // Parsing `(elem sep)* elem?` would parse the last element twice, once as
+    // a failed attempt at `elem sep`. Since this nests, it would make parsing
+    // exponential in the nesting depth.
+    let rest = separator
+        .clone()
+        .ignore_then(space.clone())
+        .then(elem.clone().then(space.clone()))
+        .repeated();
+    let trailing = separator.ignore_then(space.clone()).or_not();
     start
         .ignore_then(space.clone())
-        .then(
-            elem.clone()
-                .then(space.clone())
-                .then_ignore(separator)
-                .then(space.clone())
-                .repeated(),
-        )
-        .then(elem.then(space).or_not())
+        .then(elem.then(space).then(rest).then(trailing).or_not())
         .then_ignore(end)
-        .map_with_span(|((s0, first_elems), last_elem), span| {
-            let mut space_before_elem = s0;
-            let mut elems = vec![];
-            for ((elem, s1), s2) in first_elems {
-                elems.push((space_before_elem, elem, s1));
-                space_before_elem = s2;
-            }
-            let trailing = if let Some((elem, s1)) = last_elem {
-                elems.push((space_before_elem, elem, s1));
-                None
-            } else {
-                Some(space_before_elem)
-            };
-            BoundedSeparated {
-                elems,
-                trailing,
+        .map_with_span(|(s0, elems), span| match elems {
+            None => BoundedSeparated {
+                elems: vec![],
+                trailing: Some(s0),
                 span,
+            },
+            Some((((first, s1), rest), trailing)) => {
+                let mut elems = vec![(s0, first, s1)];
+                for (s0, (elem, s1)) in rest {
+                    elems.push((s0, elem, s1));
+                }
+                BoundedSeparated {
+                    elems,
+                    trailing,
+                    span,
+                }
             }
         })
         .boxed()
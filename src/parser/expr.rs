@@ -5,7 +5,7 @@ use chumsky::prelude::*;
 use crate::ast::{BinOp, Expr, FuncDef, Lit, Space, TableConstr, TableDestr, Var};
 use crate::span::HasSpan;
 
-use super::basic::{EParser, Error};
+use super::basic::{keyword, recover_delimited, EParser, Error};
 
 fn atom_paren(
     space: EParser<Space>,
@@ -35,10 +35,11 @@ pub fn atom(
 ) -> EParser<Expr> {
     let lit = lit.map(Expr::Lit);
     let var = var.map(Expr::Var);
-    let table_constr = table_constr.map(Expr::TableConstr);
+    let table_constr =
+        recover_delimited('{', '}', table_constr.map(Expr::TableConstr), Expr::Error);
     let table_destr = table_destr.map(Expr::TableDestr);
     let func_def = func_def.map(Expr::FuncDef);
-    let paren = atom_paren(space, expr);
+    let paren = recover_delimited('(', ')', atom_paren(space, expr), Expr::Error);
 
     lit.or(paren)
         .or(table_destr)
@@ -77,23 +78,38 @@ fn right_assoc(
     op: impl Parser<char, BinOp, Error = Error> + 'static,
     over: impl Parser<char, Expr, Error = Error> + Clone + 'static,
 ) -> BoxedParser<'static, char, Expr, Error> {
-    let over_op = over
+    let op_over = space
         .clone()
-        .then(space.clone())
         .then(op)
         .then(space)
-        .map(|(((left, s0), op), s1)| (left, s0, op, s1));
+        .then(over.clone())
+        .map(|(((s0, op), s1), right)| (s0, op, s1, right));
 
-    over_op
-        .repeated()
-        .then(over)
-        .foldr(|(left, s0, op, s1), right| Expr::BinOp {
-            span: left.span().join(right.span()),
-            left: left.boxed(),
-            s0,
-            op,
-            s1,
-            right: right.boxed(),
+    // Parsing `(over op)* over` would parse the last operand twice, once as a
+    // failed attempt at `over op`. Since this nests, it would make parsing
+    // exponential in the nesting depth.
+    over.then(op_over.repeated())
+        .map(|(first, rest)| {
+            let mut operands = vec![first];
+            let mut ops = vec![];
+            for (s0, op, s1, right) in rest {
+                ops.push((s0, op, s1));
+                operands.push(right);
+            }
+
+            let mut right = operands.pop().unwrap();
+            while let Some((s0, op, s1)) = ops.pop() {
+                let left = operands.pop().unwrap();
+                right = Expr::BinOp {
+                    span: left.span().join(right.span()),
+                    left: left.boxed(),
+                    s0,
+                    op,
+                    s1,
+                    right: right.boxed(),
+                };
+            }
+            right
         })
         .boxed()
 }
@@ -110,16 +126,16 @@ pub fn expr(space: EParser<Space>, prefixed: EParser<Expr>) -> EParser<Expr> {
     // == != > >= < <=
     let op_prec_2 = (just("==").to(BinOp::Eq))
         .or(just("!=").to(BinOp::Neq))
-        .or(just('>').to(BinOp::Gt))
         .or(just(">=").to(BinOp::Ge))
-        .or(just('<').to(BinOp::Lt))
-        .or(just("<=").to(BinOp::Le));
+        .or(just('>').to(BinOp::Gt))
+        .or(just("<=").to(BinOp::Le))
+        .or(just('<').to(BinOp::Lt));
 
     // and
-    let op_prec_1 = text::keyword("and").to(BinOp::And);
+    let op_prec_1 = keyword("and").to(BinOp::And);
 
     // or
-    let op_prec_0 = text::keyword("or").to(BinOp::Or);
+    let op_prec_0 = keyword("or").to(BinOp::Or);
 
     right_assoc(
         space.clone(),
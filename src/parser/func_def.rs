@@ -2,13 +2,13 @@ use chumsky::prelude::*;
 
 use crate::ast::{Expr, FuncDef, Ident, Space, TablePattern};
 
-use super::basic::{EParser, Error};
+use super::basic::{keyword, EParser, Error};
 
 fn func_def_anon_no_arg(
     space: EParser<Space>,
     expr: EParser<Expr>,
 ) -> impl Parser<char, FuncDef, Error = Error> {
-    text::keyword("function")
+    keyword("function")
         .ignore_then(space.clone())
         .then_ignore(just('('))
         .then(space.clone())
@@ -29,7 +29,7 @@ fn func_def_anon_arg(
     ident: EParser<Ident>,
     expr: EParser<Expr>,
 ) -> impl Parser<char, FuncDef, Error = Error> {
-    text::keyword("function")
+    keyword("function")
         .ignore_then(space.clone())
         .then_ignore(just('('))
         .then(space.clone())
@@ -56,7 +56,7 @@ fn func_def_anon_destr(
     table_pattern: EParser<TablePattern>,
     expr: EParser<Expr>,
 ) -> impl Parser<char, FuncDef, Error = Error> {
-    text::keyword("function")
+    keyword("function")
         .ignore_then(space.clone())
         .then(table_pattern)
         .then(space)
@@ -77,7 +77,7 @@ fn func_def_named_no_arg(
     expr: EParser<Expr>,
 ) -> impl Parser<char, FuncDef, Error = Error> {
     local
-        .then_ignore(text::keyword("function"))
+        .then_ignore(keyword("function"))
         .then(space.clone())
         .then(ident)
         .then(space.clone())
@@ -107,7 +107,7 @@ fn func_def_named_arg(
     expr: EParser<Expr>,
 ) -> impl Parser<char, FuncDef, Error = Error> {
     local
-        .then_ignore(text::keyword("function"))
+        .then_ignore(keyword("function"))
         .then(space.clone())
         .then(ident.clone())
         .then(space.clone())
@@ -142,7 +142,7 @@ fn func_def_named_destr(
     expr: EParser<Expr>,
 ) -> impl Parser<char, FuncDef, Error = Error> {
     local
-        .then_ignore(text::keyword("function"))
+        .then_ignore(keyword("function"))
         .then(space.clone())
         .then(ident)
         .then(space.clone())
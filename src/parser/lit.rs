@@ -3,27 +3,45 @@
 use chumsky::prelude::*;
 
 use crate::ast::{
-    Expr, Ident, Lit, NumLit, NumLitStr, Space, StringLit, StringLitElem, TableLit, TableLitElem,
+    Expr, FloatLit, Ident, Lit, NumLit, NumLitStr, Space, StringLit, StringLitElem, TableLit,
+    TableLitElem,
 };
 use crate::builtin::Builtin;
+use crate::span::Span;
 
-use super::basic::{bounded_separated, EParser, Error};
+use super::basic::{bounded_separated, keyword, recover_elem, EParser, Error};
 
 fn builtin_lit() -> impl Parser<char, Builtin, Error = Error> {
     just('\'').ignore_then(choice((
-        text::keyword("get").to(Builtin::Get),
-        text::keyword("set").to(Builtin::Set),
-        text::keyword("getraw").to(Builtin::GetRaw),
-        text::keyword("setraw").to(Builtin::SetRaw),
-        text::keyword("getmeta").to(Builtin::GetMeta),
-        text::keyword("setmeta").to(Builtin::SetMeta),
-        text::keyword("scope").to(Builtin::Scope),
-        text::keyword("arg").to(Builtin::Arg),
-        text::keyword("destructure").to(Builtin::Destructure),
+        keyword("get").to(Builtin::Get),
+        keyword("set").to(Builtin::Set),
+        keyword("getraw").to(Builtin::GetRaw),
+        keyword("setraw").to(Builtin::SetRaw),
+        keyword("getmeta").to(Builtin::GetMeta),
+        keyword("setmeta").to(Builtin::SetMeta),
+        keyword("scope").to(Builtin::Scope),
+        keyword("arg").to(Builtin::Arg),
+        keyword("destructure").to(Builtin::Destructure),
+        keyword("neg").to(Builtin::Neg),
+        keyword("not").to(Builtin::Not),
+        keyword("mul").to(Builtin::Mul),
+        keyword("div").to(Builtin::Div),
+        keyword("mod").to(Builtin::Mod),
+        keyword("add").to(Builtin::Add),
+        keyword("sub").to(Builtin::Sub),
+        keyword("eq").to(Builtin::Eq),
+        keyword("ne").to(Builtin::Ne),
+        keyword("gt").to(Builtin::Gt),
+        keyword("ge").to(Builtin::Ge),
+        keyword("lt").to(Builtin::Lt),
+        keyword("le").to(Builtin::Le),
+        keyword("and").to(Builtin::And),
+        keyword("or").to(Builtin::Or),
     )))
 }
 
-fn num_lit_str_radix(radix: u32) -> impl Parser<char, (i64, NumLitStr), Error = Error> {
+/// The value of an integer literal's digits, which may contain underscores.
+fn int_value(radix: u32, s: String, span: Span) -> Result<(i64, NumLitStr), Error> {
     // Minimum amount of digits required to represent i64::MAX. The rest of this
     // code assumes that any value that can be represented using this amount of
     // digits fits into an u64.
@@ -49,37 +67,87 @@ fn num_lit_str_radix(radix: u32) -> impl Parser<char, (i64, NumLitStr), Error =
         _ => panic!("unsupported radix"),
     };
 
+    let digits = s.chars().filter(|c| *c != '_').collect::<String>();
+    if digits.is_empty() {
+        let msg = "integer literal needs to contain at least one digit";
+        return Err(Simple::custom(span, msg));
+    } else if digits.len() > max_digits {
+        let msg = format!("integer literal too large, the maximum value is {max_value}");
+        return Err(Simple::custom(span, msg));
+    }
+
+    let value = u64::from_str_radix(&digits, radix).unwrap();
+    if value <= i64::MAX as u64 {
+        Ok((value as i64, constructor(s)))
+    } else {
+        let msg = format!("integer literal too large, the maximum value is {max_value}");
+        Err(Simple::custom(span, msg))
+    }
+}
+
+fn num_lit_str_radix(radix: u32) -> impl Parser<char, (i64, NumLitStr), Error = Error> {
     filter(move |c: &char| c.is_digit(radix) || *c == '_')
         .repeated()
         .at_least(1)
         .collect::<String>()
-        .try_map(move |s, span| {
-            let digits = s.chars().filter(|c| *c != '_').collect::<String>();
-            if digits.is_empty() {
-                let msg = "integer literal needs to contain at least one digit";
-                return Err(Simple::custom(span, msg));
-            } else if digits.len() > max_digits {
-                let msg = format!("integer literal too large, the maximum value is {max_value}");
-                return Err(Simple::custom(span, msg));
-            }
-
-            let value = u64::from_str_radix(&digits, radix).unwrap();
-            if value <= i64::MAX as u64 {
-                Ok((value as i64, constructor(s)))
-            } else {
-                let msg = format!("integer literal too large, the maximum value is {max_value}");
-                Err(Simple::custom(span, msg))
-            }
-        })
+        .try_map(move |s, span| int_value(radix, s, span))
 }
 
-fn num_lit() -> impl Parser<char, NumLit, Error = Error> {
+/// A binary or hexadecimal integer literal.
+fn radix_num_lit() -> impl Parser<char, NumLit, Error = Error> {
     (just("0b").ignore_then(num_lit_str_radix(2)))
         .or(just("0x").ignore_then(num_lit_str_radix(16)))
-        .or(num_lit_str_radix(10))
         .map_with_span(|(value, str), span| NumLit { value, str, span })
 }
 
+/// A decimal literal. It is a float literal if it has a fraction or an
+/// exponent, and an integer literal otherwise.
+///
+/// Both kinds share their leading digits, so they are parsed together. Parsing
+/// them as alternatives would hide the errors of one behind the other.
+fn dec_lit() -> impl Parser<char, Lit, Error = Error> {
+    let digits = || {
+        filter(|c: &char| c.is_ascii_digit() || *c == '_')
+            .repeated()
+            .at_least(1)
+    };
+
+    // The integer part must start with a digit, otherwise `_1` and `_1e5`
+    // would be numbers and not identifiers.
+    let integer = filter(|c: &char| c.is_ascii_digit())
+        .chain::<char, _, _>(digits().or_not().flatten())
+        .collect::<String>();
+    let fraction = just('.').chain::<char, _, _>(digits());
+    let exponent = one_of("eE")
+        .chain::<char, _, _>(one_of("+-").or_not())
+        .chain::<char, _, _>(digits());
+
+    integer
+        .then(fraction.or_not())
+        .then(exponent.or_not())
+        .try_map(|((integer, fraction), exponent), span| {
+            if fraction.is_none() && exponent.is_none() {
+                let (value, str) = int_value(10, integer, span)?;
+                return Ok(Lit::Num(NumLit { value, str, span }));
+            }
+
+            let str = integer
+                .chars()
+                .chain(fraction.into_iter().flatten())
+                .chain(exponent.into_iter().flatten())
+                .collect::<String>();
+            let digits = str.chars().filter(|c| *c != '_').collect::<String>();
+            let value = digits
+                .parse::<f64>()
+                .map_err(|_| Simple::custom(span, "invalid float literal"))?;
+            if value.is_finite() {
+                Ok(Lit::Float(FloatLit { value, str, span }))
+            } else {
+                Err(Simple::custom(span, "float literal too large"))
+            }
+        })
+}
+
 fn string_lit_elem() -> impl Parser<char, StringLitElem, Error = Error> {
     let plain = filter(|c: &char| !matches!(c, '\\' | '"' | '\t' | '\r' | '\n'))
         .repeated()
@@ -154,29 +222,33 @@ fn table_lit(
     space: EParser<Space>,
     table_lit_elem: EParser<TableLitElem>,
 ) -> impl Parser<char, TableLit, Error = Error> {
+    let elem = recover_elem(space.clone(), [',', '}'], table_lit_elem, |span| {
+        TableLitElem::Positional(Expr::Error(span).boxed())
+    });
     bounded_separated(
         space,
         just("'{").to(()),
         just('}').to(()),
         just(',').to(()),
-        table_lit_elem,
+        elem,
     )
     .map(TableLit)
 }
 
 pub fn lit(space: EParser<Space>, table_lit_elem: EParser<TableLitElem>) -> EParser<Lit> {
-    let nil = text::keyword("nil").map_with_span(|_, span| Lit::Nil(span));
-    let r#true = text::keyword("true").map_with_span(|_, span| Lit::Bool(true, span));
-    let r#false = text::keyword("false").map_with_span(|_, span| Lit::Bool(false, span));
+    let nil = keyword("nil").map_with_span(|_, span| Lit::Nil(span));
+    let r#true = keyword("true").map_with_span(|_, span| Lit::Bool(true, span));
+    let r#false = keyword("false").map_with_span(|_, span| Lit::Bool(false, span));
     let builtin = builtin_lit().map_with_span(Lit::Builtin);
-    let num = num_lit().map(Lit::Num);
+    let radix_num = radix_num_lit().map(Lit::Num);
     let string = string_lit().map(Lit::String);
     let table = table_lit(space, table_lit_elem).map(Lit::Table);
 
     nil.or(r#true)
         .or(r#false)
         .or(builtin)
-        .or(num)
+        .or(radix_num)
+        .or(dec_lit())
         .or(string)
         .or(table)
         .boxed()
@@ -5,7 +5,7 @@ use chumsky::prelude::*;
 use crate::ast::{Expr, Space};
 use crate::span::{HasSpan, Span};
 
-use super::basic::{EParser, Error};
+use super::basic::{keyword, EParser, Error};
 
 enum Prefix {
     /// See [`Expr::Neg`].
@@ -43,7 +43,7 @@ fn prefix_neg(space: EParser<Space>) -> impl Parser<char, Prefix, Error = Error>
 }
 
 fn prefix_not(space: EParser<Space>) -> impl Parser<char, Prefix, Error = Error> {
-    text::keyword("not")
+    keyword("not")
         .map_with_span(|_, span| span)
         .then(space)
         .map(|(not, s0)| Prefix::Not { not, s0 })
@@ -4,7 +4,7 @@ use chumsky::prelude::*;
 
 use crate::ast::{Expr, Program, Space, TableLitElem};
 
-use super::basic::{bounded_separated, EParser};
+use super::basic::{bounded_separated, keyword, recover_elem, EParser};
 
 pub fn program(
     space: EParser<Space>,
@@ -13,19 +13,22 @@ pub fn program(
 ) -> EParser<Program> {
     let lit = space
         .clone()
-        .then(expr)
+        .then(expr.map(Expr::boxed))
         .then(space.clone())
         .map_with_span(|((s0, expr), s1), span| Program::Expr { s0, expr, s1, span });
 
+    let elem = recover_elem(space.clone(), [','], table_lit_elem, |span| {
+        TableLitElem::Positional(Expr::Error(span).boxed())
+    });
     let module = space
         .clone()
-        .then_ignore(text::keyword("module"))
+        .then_ignore(keyword("module"))
         .then(bounded_separated(
             space,
             empty(),
             empty(),
             just(',').to(()),
-            table_lit_elem,
+            elem,
         ))
         .map_with_span(|(s0, elems), span| Program::Module { s0, elems, span });
 
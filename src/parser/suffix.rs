@@ -5,7 +5,7 @@ use chumsky::prelude::*;
 use crate::ast::{Call, Expr, Field, Ident, Space, TableConstr};
 use crate::span::{HasSpan, Span};
 
-use super::basic::{EParser, Error};
+use super::basic::{recover_delimited, EParser, Error};
 
 enum Suffix {
     /// See [`Call::Arg`].
@@ -53,6 +53,9 @@ enum Suffix {
         s3: Space,
         value: Box<Expr>,
     },
+
+    /// A suffix that could not be parsed, see [`Expr::Error`].
+    Error,
 }
 
 impl Suffix {
@@ -137,6 +140,8 @@ impl Suffix {
                 span,
             }
             .expr(),
+
+            Self::Error => Expr::Error(span),
         }
     }
 }
@@ -145,18 +150,23 @@ fn suffix_call_arg(
     space: EParser<Space>,
     expr: EParser<Expr>,
 ) -> impl Parser<char, Suffix, Error = Error> {
-    space
-        .clone()
-        .then_ignore(just('('))
-        .then(space.clone())
+    let parens = just('(')
+        .ignore_then(space.clone())
         .then(expr)
-        .then(space)
+        .then(space.clone())
         .then_ignore(just(')'))
-        .map(|(((s0, s1), arg), s2)| Suffix::CallArg {
-            s0,
-            s1,
-            arg: arg.boxed(),
-            s2,
+        .map(Some);
+
+    space
+        .then(recover_delimited('(', ')', parens, |_| None))
+        .map(|(s0, parens)| match parens {
+            Some(((s1, arg), s2)) => Suffix::CallArg {
+                s0,
+                s1,
+                arg: arg.boxed(),
+                s2,
+            },
+            None => Suffix::Error,
         })
 }
 
@@ -174,46 +184,48 @@ fn suffix_call_constr(
     table_constr: EParser<TableConstr>,
 ) -> impl Parser<char, Suffix, Error = Error> {
     space
-        .then(table_constr)
-        .map(|(s0, constr)| Suffix::CallConstr { s0, constr })
+        .then(recover_delimited('{', '}', table_constr.map(Some), |_| {
+            None
+        }))
+        .map(|(s0, constr)| match constr {
+            Some(constr) => Suffix::CallConstr { s0, constr },
+            None => Suffix::Error,
+        })
 }
 
-fn suffix_field_access(
+/// Parse `[index]` optionally followed by `= value`.
+///
+/// Access and assignment share their prefix, so they are parsed together.
+/// Trying them one after the other would parse the index twice.
+fn suffix_field(
     space: EParser<Space>,
     expr: EParser<Expr>,
 ) -> impl Parser<char, Suffix, Error = Error> {
-    space
+    let assign = space
         .clone()
-        .then_ignore(just('['))
+        .then_ignore(just('='))
         .then(space.clone())
+        .then(expr.clone());
+
+    let brackets = just('[')
+        .ignore_then(space.clone())
         .then(expr)
-        .then(space)
+        .then(space.clone())
         .then_ignore(just(']'))
-        .map(|(((s0, s1), index), s2)| Suffix::FieldAccess {
-            s0,
-            s1,
-            index: index.boxed(),
-            s2,
-        })
-}
+        .map(Some);
 
-fn suffix_field_assign(
-    space: EParser<Space>,
-    expr: EParser<Expr>,
-) -> impl Parser<char, Suffix, Error = Error> {
     space
-        .clone()
-        .then_ignore(just('['))
-        .then(space.clone())
-        .then(expr.clone())
-        .then(space.clone())
-        .then_ignore(just(']'))
-        .then(space.clone())
-        .then_ignore(just('='))
-        .then(space)
-        .then(expr)
-        .map(
-            |((((((s0, s1), index), s2), s3), s4), value)| Suffix::FieldAssign {
+        .then(recover_delimited('[', ']', brackets, |_| None))
+        .then(assign.or_not())
+        .map(|((s0, brackets), assign)| match (brackets, assign) {
+            (None, _) => Suffix::Error,
+            (Some(((s1, index), s2)), None) => Suffix::FieldAccess {
+                s0,
+                s1,
+                index: index.boxed(),
+                s2,
+            },
+            (Some(((s1, index), s2)), Some(((s3, s4), value))) => Suffix::FieldAssign {
                 s0,
                 s1,
                 index: index.boxed(),
@@ -222,37 +234,30 @@ fn suffix_field_assign(
                 s4,
                 value: value.boxed(),
             },
-        )
+        })
 }
 
-fn suffix_field_access_ident(
+/// Parse `.ident` optionally followed by `= value`, see [`suffix_field`].
+fn suffix_field_ident(
     space: EParser<Space>,
     ident: EParser<Ident>,
+    expr: EParser<Expr>,
 ) -> impl Parser<char, Suffix, Error = Error> {
-    space
+    let assign = space
         .clone()
-        .then_ignore(just('.'))
-        .then(space)
-        .then(ident)
-        .map(|((s0, s1), ident)| Suffix::FieldAccessIdent { s0, s1, ident })
-}
+        .then_ignore(just('='))
+        .then(space.clone())
+        .then(expr);
 
-fn suffix_field_assign_ident(
-    space: EParser<Space>,
-    ident: EParser<Ident>,
-    expr: EParser<Expr>,
-) -> impl Parser<char, Suffix, Error = Error> {
     space
         .clone()
         .then_ignore(just('.'))
-        .then(space.clone())
-        .then(ident)
-        .then(space.clone())
-        .then_ignore(just('='))
         .then(space)
-        .then(expr)
-        .map(
-            |(((((s0, s1), ident), s2), s3), value)| Suffix::FieldAssignIdent {
+        .then(ident)
+        .then(assign.or_not())
+        .map(|(((s0, s1), ident), assign)| match assign {
+            None => Suffix::FieldAccessIdent { s0, s1, ident },
+            Some(((s2, s3), value)) => Suffix::FieldAssignIdent {
                 s0,
                 s1,
                 ident,
@@ -260,7 +265,7 @@ fn suffix_field_assign_ident(
                 s3,
                 value: value.boxed(),
             },
-        )
+        })
 }
 
 pub fn suffixed(
@@ -273,18 +278,14 @@ pub fn suffixed(
     let call_arg = suffix_call_arg(space.clone(), expr.clone());
     let call_no_arg = suffix_call_no_arg(space.clone());
     let call_constr = suffix_call_constr(space.clone(), table_constr);
-    let field_access = suffix_field_access(space.clone(), expr.clone());
-    let field_assign = suffix_field_assign(space.clone(), expr.clone());
-    let field_access_ident = suffix_field_access_ident(space.clone(), ident.clone());
-    let field_assign_ident = suffix_field_assign_ident(space, ident, expr);
+    let field = suffix_field(space.clone(), expr.clone());
+    let field_ident = suffix_field_ident(space, ident, expr);
 
     let suffix = call_arg
         .or(call_no_arg)
         .or(call_constr)
-        .or(field_assign)
-        .or(field_access)
-        .or(field_assign_ident)
-        .or(field_access_ident)
+        .or(field)
+        .or(field_ident)
         .map_with_span(|suffix, span| (suffix, span));
 
     atom.then(suffix.repeated())
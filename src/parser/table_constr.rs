@@ -4,7 +4,7 @@ use chumsky::prelude::*;
 
 use crate::ast::{Expr, Space, TableConstr, TableConstrElem, TableLitElem};
 
-use super::basic::{bounded_separated, EParser, Error};
+use super::basic::{bounded_separated, recover_elem, EParser, Error};
 
 fn table_constr_elem(
     space: EParser<Space>,
@@ -43,6 +43,9 @@ pub fn table_constr(
     expr: EParser<Expr>,
 ) -> EParser<TableConstr> {
     let elem = table_constr_elem(space.clone(), table_lit_elem, expr);
+    let elem = recover_elem(space.clone(), [',', '}'], elem, |span| {
+        TableConstrElem::Lit(TableLitElem::Positional(Expr::Error(span).boxed()))
+    });
     bounded_separated(
         space,
         just('{').to(()),
@@ -2,54 +2,163 @@
 
 use chumsky::prelude::*;
 
-use crate::ast::{Expr, Ident, Space, TableDestr, TablePattern, TablePatternElem};
+use crate::ast::{Expr, Ident, Pattern, Space, TableDestr, TablePattern, TablePatternElem};
 
-use super::basic::{bounded_separated, EParser, Error};
+use super::basic::{bounded_separated, lookahead, EParser, Error};
 
 fn table_pattern_elem(
     space: EParser<Space>,
     ident: EParser<Ident>,
+    table_pattern: impl Parser<char, TablePattern, Error = Error> + Clone,
+    expr: EParser<Expr>,
 ) -> impl Parser<char, TablePatternElem, Error = Error> + Clone {
-    let positional = ident.clone().map(TablePatternElem::Positional);
+    let pattern = ident
+        .clone()
+        .map(Pattern::Ident)
+        .or(table_pattern.map(Pattern::Table));
+
+    let default = space
+        .clone()
+        .then_ignore(just('='))
+        .then(space.clone())
+        .then(expr);
+
+    let positional = pattern
+        .clone()
+        .then(default.clone().or_not())
+        .map_with_span(|(pattern, default), span| match default {
+            None => TablePatternElem::Positional(pattern),
+            Some(((s0, s1), default)) => TablePatternElem::PositionalDefault {
+                pattern,
+                s0,
+                s1,
+                default: default.boxed(),
+                span,
+            },
+        });
 
     let named = ident
         .clone()
         .then(space.clone())
         .then_ignore(just(':'))
-        .then(space)
+        .then(space.clone())
+        .then(pattern)
+        .then(default.or_not())
+        .map_with_span(
+            |((((name, s0), s1), pattern), default), span| match default {
+                None => TablePatternElem::Named {
+                    name,
+                    s0,
+                    s1,
+                    pattern,
+                    span,
+                },
+                Some(((s2, s3), default)) => TablePatternElem::NamedDefault {
+                    name,
+                    s0,
+                    s1,
+                    pattern,
+                    s2,
+                    s3,
+                    default: default.boxed(),
+                    span,
+                },
+            },
+        );
+
+    let rest = just("..")
+        .ignore_then(space)
         .then(ident)
-        .map_with_span(|(((name, s0), s1), ident), span| TablePatternElem::Named {
-            name,
-            s0,
-            s1,
-            ident,
-            span,
-        });
+        .map_with_span(|(s0, name), span| TablePatternElem::Rest { s0, name, span });
 
-    named.or(positional)
+    rest.or(named).or(positional)
 }
 
-pub fn table_pattern(space: EParser<Space>, ident: EParser<Ident>) -> EParser<TablePattern> {
-    let elem = table_pattern_elem(space.clone(), ident);
-    bounded_separated(
-        space,
-        just('{').to(()),
-        just('}').to(()),
-        just(',').to(()),
-        elem,
-    )
-    .map(TablePattern)
+pub fn table_pattern(
+    space: EParser<Space>,
+    ident: EParser<Ident>,
+    expr: EParser<Expr>,
+) -> EParser<TablePattern> {
+    recursive(|table_pattern| {
+        let elem = table_pattern_elem(space.clone(), ident, table_pattern, expr);
+        bounded_separated(
+            space,
+            just('{').to(()),
+            just('}').to(()),
+            just(',').to(()),
+            elem,
+        )
+        .try_map(|elems, span| {
+            let rests = elems
+                .elems
+                .iter()
+                .filter(|(_, e, _)| matches!(e, TablePatternElem::Rest { .. }))
+                .count();
+            if rests > 1 {
+                Err(Error::custom(
+                    span,
+                    "a pattern can have at most one rest capture",
+                ))
+            } else {
+                Ok(TablePattern(elems))
+            }
+        })
+    })
     .boxed()
 }
 
+/// Skip over a `{ .. }` group without parsing its contents, only keeping
+/// track of nested braces, strings and comments.
+fn braces() -> impl Parser<char, (), Error = Error> + Clone {
+    recursive(|braces| {
+        let escaped = just('\\').ignore_then(any()).ignored();
+        let string = escaped
+            .or(none_of("\"").ignored())
+            .repeated()
+            .delimited_by(just('"'), just('"'))
+            .ignored();
+        let comment = just('#').ignore_then(take_until(just('\n'))).ignored();
+        let other = none_of("{}\"#").ignored();
+
+        braces
+            .or(string)
+            .or(comment)
+            .or(other)
+            .repeated()
+            .delimited_by(just('{'), just('}'))
+            .ignored()
+    })
+}
+
 pub fn table_destr(
     space: EParser<Space>,
     local: EParser<Option<Space>>,
     table_pattern: EParser<TablePattern>,
     expr: EParser<Expr>,
 ) -> EParser<TableDestr> {
-    local
-        .then(table_pattern)
+    // A pattern's defaults may contain whole expressions, so trying to parse a
+    // table constructor as a pattern first and backtracking would parse it
+    // twice, once per nesting level. Instead, only parse a pattern if the
+    // braces are followed by an `=` (but not `==`). After `local`, they can't
+    // be a table constructor anyways.
+    let assign_ahead = lookahead(
+        braces()
+            .then(space.clone())
+            .then(just('='))
+            .then(none_of("=").ignored().or(end())),
+    )
+    .boxed();
+    let pattern = local.then_with(move |local| {
+        let ahead = match local {
+            Some(_) => empty().boxed(),
+            None => assign_ahead.clone(),
+        };
+        ahead
+            .ignore_then(table_pattern.clone())
+            .map(move |pattern| (local.clone(), pattern))
+    });
+
+    pattern
         .then(space.clone())
         .then_ignore(just('='))
         .then(space)
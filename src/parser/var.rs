@@ -6,71 +6,76 @@ use crate::ast::{Expr, Ident, Space, Var};
 
 use super::basic::{EParser, Error};
 
-fn var_access(space: EParser<Space>, expr: EParser<Expr>) -> impl Parser<char, Var, Error = Error> {
-    just('[')
-        .ignore_then(space.clone())
-        .then(expr)
-        .then(space)
-        .then_ignore(just(']'))
-        .map_with_span(|((s0, index), s1), span| Var::Access {
-            s0,
-            index: index.boxed(),
-            s1,
-            span,
-        })
-}
-
-fn var_assign(
+/// Parse `[index]` optionally followed by `= value`, with `local` only allowed
+/// for assignments.
+///
+/// Access and assignment share their prefix, so they are parsed together.
+/// Trying them one after the other would parse the index twice.
+fn var_index(
     space: EParser<Space>,
     local: EParser<Option<Space>>,
     expr: EParser<Expr>,
 ) -> impl Parser<char, Var, Error = Error> {
+    let assign = space
+        .clone()
+        .then_ignore(just('='))
+        .then(space.clone())
+        .then(expr.clone());
+
     local
         .then_ignore(just('['))
         .then(space.clone())
-        .then(expr.clone())
-        .then(space.clone())
-        .then_ignore(just(']'))
-        .then(space.clone())
-        .then_ignore(just('='))
-        .then(space)
         .then(expr)
-        .map_with_span(
-            |((((((local, s0), index), s1), s2), s3), value), span| Var::Assign {
-                local,
-                s0,
-                index: index.boxed(),
-                s1,
-                s2,
-                s3,
-                value: value.boxed(),
-                span,
+        .then(space)
+        .then_ignore(just(']'))
+        .then(assign.or_not())
+        .try_map(
+            |((((local, s0), index), s1), assign), span| match (local, assign) {
+                (None, None) => Ok(Var::Access {
+                    s0,
+                    index: index.boxed(),
+                    s1,
+                    span,
+                }),
+                (local, Some(((s2, s3), value))) => Ok(Var::Assign {
+                    local,
+                    s0,
+                    index: index.boxed(),
+                    s1,
+                    s2,
+                    s3,
+                    value: value.boxed(),
+                    span,
+                }),
+                (Some(_), None) => Err(Error::expected_input_found(span, [Some('=')], None)),
             },
         )
 }
 
-fn var_assign_ident(
+/// Parse `ident` optionally followed by `= value`, see [`var_index`].
+fn var_ident(
     space: EParser<Space>,
     ident: EParser<Ident>,
     local: EParser<Option<Space>>,
     expr: EParser<Expr>,
 ) -> impl Parser<char, Var, Error = Error> {
+    let assign = space.clone().then_ignore(just('=')).then(space).then(expr);
+
     local
         .then(ident)
-        .then(space.clone())
-        .then_ignore(just('='))
-        .then(space)
-        .then(expr)
-        .map_with_span(
-            |((((local, name), s0), s1), value), span| Var::AssignIdent {
+        .then(assign.or_not())
+        .try_map(|((local, name), assign), span| match (local, assign) {
+            (None, None) => Ok(Var::AccessIdent(name)),
+            (local, Some(((s0, s1), value))) => Ok(Var::AssignIdent {
                 local,
                 name,
                 s0,
                 s1,
                 value: value.boxed(),
                 span,
-            },
-        )
+            }),
+            (Some(_), None) => Err(Error::expected_input_found(span, [Some('=')], None)),
+        })
 }
 
 pub fn var(
@@ -79,10 +84,8 @@ pub fn var(
     local: EParser<Option<Space>>,
     expr: EParser<Expr>,
 ) -> EParser<Var> {
-    let access = var_access(space.clone(), expr.clone());
-    let assign = var_assign(space.clone(), local.clone(), expr.clone());
-    let access_ident = ident.clone().map(Var::AccessIdent);
-    let assign_ident = var_assign_ident(space, ident, local, expr);
+    let index = var_index(space.clone(), local.clone(), expr.clone());
+    let ident = var_ident(space, ident, local, expr);
 
-    assign.or(access).or(assign_ident).or(access_ident).boxed()
+    index.or(ident).boxed()
 }
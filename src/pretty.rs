@@ -1,6 +1,3 @@
-// TODO Remove this and print whitespace and comments properly
-#![allow(unused_variables)]
-
 use pretty::{Pretty, RcAllocator};
 
 mod basic;
@@ -25,3 +22,38 @@ pub fn pretty_to_string<P: Pretty<'static, RcAllocator>>(p: P, width: usize) ->
     s.push('\n');
     s
 }
+
+/// The comments of a source text without syntax errors, in order. Trailing
+/// whitespace is removed since pretty printing removes it too.
+///
+/// Pretty printing a program must keep its comments, but comparing programs
+/// with [`SpanlessEq`](crate::ast::SpanlessEq) ignores them.
+pub fn comments(text: &str) -> Vec<&str> {
+    let mut comments = vec![];
+    let mut chars = text.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            // Skip string literals, which may contain `#` and escaped quotes.
+            '"' => {
+                while let Some((_, c)) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '#' => {
+                let end = chars
+                    .by_ref()
+                    .find(|(_, c)| *c == '\n')
+                    .map_or(text.len(), |(j, _)| j);
+                comments.push(text[i + 1..end].trim_end());
+            }
+            _ => {}
+        }
+    }
+    comments
+}
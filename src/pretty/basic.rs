@@ -1,6 +1,8 @@
+use std::mem;
+
 use pretty::{DocAllocator, DocBuilder, Pretty};
 
-use crate::ast::{BoundedSeparated, Ident};
+use crate::ast::{BoundedSeparated, Ident, Line, Space};
 
 use super::NEST_DEPTH;
 
@@ -10,7 +12,206 @@ impl<'a, D: DocAllocator<'a>> Pretty<'a, D> for Ident {
     }
 }
 
+/// Print the comments and blank lines of consecutive spaces. Spaces are
+/// separated by code that has already been printed, like the `,` between the
+/// elements of a table.
+///
+/// A comment on the same line as the code before it stays on that line, all
+/// other comments get a line of their own. Multiple blank lines are collapsed
+/// into one. Blank lines are dropped if there is no code before (`after_code`)
+/// or after (`before_code`) the spaces, like at the start of the file or before
+/// a closing delimiter.
+///
+/// Returns `None` if there is nothing to print. Otherwise, the document forces
+/// its enclosing group to break and must be followed by a line break.
+pub fn comments<'a, D: DocAllocator<'a>>(
+    allocator: &'a D,
+    spaces: Vec<Space>,
+    after_code: bool,
+    before_code: bool,
+) -> Option<DocBuilder<'a, D>> {
+    let mut docs = vec![];
+    // Whether the source line of the code before has ended.
+    let mut line_ended = !after_code;
+    // Whether the current output line already ends in a comment.
+    let mut comment_on_line = false;
+    let mut blank = false;
+
+    for space in spaces {
+        for line in space.lines {
+            match line {
+                Line::Empty => {
+                    if line_ended && (after_code || !docs.is_empty()) {
+                        blank = true;
+                    }
+                    line_ended = true;
+                }
+                Line::Comment(text) => {
+                    let comment = allocator.text(format!("#{}", text.trim_end()));
+                    if mem::take(&mut blank) {
+                        docs.push(allocator.text("\n"));
+                    }
+                    if !line_ended && !comment_on_line {
+                        docs.push(allocator.text(" ").append(comment));
+                    } else if docs.is_empty() && !after_code {
+                        docs.push(comment);
+                    } else {
+                        docs.push(allocator.hardline().append(comment));
+                    }
+                    comment_on_line = true;
+                    line_ended = true;
+                }
+            }
+        }
+        line_ended = false;
+    }
+
+    if blank && before_code {
+        docs.push(allocator.text("\n"));
+    }
+    if docs.is_empty() {
+        return None;
+    }
+    let force_break = allocator.nil().flat_alt(allocator.fail());
+    Some(allocator.concat(docs).append(force_break))
+}
+
+/// Print the comments and blank lines of consecutive spaces followed by a line
+/// break, or `sep` if there are none. See [`comments`].
+pub fn spaces_or<'a, D: DocAllocator<'a>>(
+    allocator: &'a D,
+    spaces: Vec<Space>,
+    sep: DocBuilder<'a, D>,
+) -> DocBuilder<'a, D> {
+    match comments(allocator, spaces, true, true) {
+        Some(comments) => comments.append(allocator.hardline()),
+        None => sep,
+    }
+}
+
+impl Space {
+    /// Print the comments and blank lines of this space followed by a line
+    /// break, or `sep` if there are none.
+    pub fn pretty_or<'a, D: DocAllocator<'a>>(
+        self,
+        allocator: &'a D,
+        sep: DocBuilder<'a, D>,
+    ) -> DocBuilder<'a, D> {
+        spaces_or(allocator, vec![self], sep)
+    }
+
+    /// Print this space followed by `doc`. If the space contains comments,
+    /// `doc` continues on an indented line after them, otherwise it follows
+    /// `sep`.
+    pub fn pretty_then<'a, D: DocAllocator<'a>>(
+        self,
+        allocator: &'a D,
+        sep: DocBuilder<'a, D>,
+        doc: DocBuilder<'a, D>,
+    ) -> DocBuilder<'a, D> {
+        match comments(allocator, vec![self], true, true) {
+            Some(comments) => comments
+                .append(allocator.hardline())
+                .append(doc)
+                .nest(NEST_DEPTH),
+            None => sep.append(doc),
+        }
+    }
+}
+
+/// Print `start s0 end` or `start s0 inner s1 end`.
+///
+/// If the spaces contain comments, `inner` is moved to a line of its own and
+/// indented, and `end` gets a line of its own.
+pub fn delimited<'a, D: DocAllocator<'a>>(
+    allocator: &'a D,
+    start: &'static str,
+    s0: Space,
+    inner: Option<(DocBuilder<'a, D>, Space)>,
+    end: &'static str,
+) -> DocBuilder<'a, D> {
+    let start = allocator.text(start);
+    let end = allocator.text(end);
+    let inner = match inner {
+        None => match comments(allocator, vec![s0], true, false) {
+            None => return start.append(end),
+            Some(comments) => comments,
+        },
+        Some((inner, s1)) => {
+            let open = comments(allocator, vec![s0], true, true);
+            let close = comments(allocator, vec![s1], true, false);
+            if open.is_none() && close.is_none() {
+                return start.append(inner).append(end);
+            }
+            open.unwrap_or_else(|| allocator.nil())
+                .append(allocator.hardline())
+                .append(inner)
+                .append(close)
+        }
+    };
+    start
+        .append(inner.nest(NEST_DEPTH))
+        .append(allocator.hardline())
+        .append(end)
+}
+
+/// Print the `local` keyword if present.
+pub fn local<'a, D: DocAllocator<'a>>(allocator: &'a D, local: Option<Space>) -> DocBuilder<'a, D> {
+    match local {
+        Some(s) => allocator
+            .text("local")
+            .append(s.pretty_or(allocator, allocator.space())),
+        None => allocator.nil(),
+    }
+}
+
 impl<E> BoundedSeparated<E> {
+    /// Print the elements, each preceded by a line break, followed by the
+    /// comments after the last element.
+    ///
+    /// Separators are printed directly after their element, so comments
+    /// between an element and its separator end up after the separator.
+    pub fn pretty_elems<'a, D, FE>(
+        self,
+        allocator: &'a D,
+        separator: DocBuilder<'a, D>,
+        elem_pretty: FE,
+    ) -> DocBuilder<'a, D>
+    where
+        D: DocAllocator<'a>,
+        D::Doc: Clone,
+        FE: Fn(E) -> DocBuilder<'a, D>,
+    {
+        let elems_empty = self.elems.is_empty();
+        let mut doc = allocator.nil();
+        let mut spaces = vec![];
+        for (i, (s0, elem, s1)) in self.elems.into_iter().enumerate() {
+            if i > 0 {
+                doc = doc.append(separator.clone());
+            }
+            spaces.push(s0);
+            doc = doc
+                .append(spaces_or(
+                    allocator,
+                    mem::take(&mut spaces),
+                    allocator.line(),
+                ))
+                .append(elem_pretty(elem));
+            spaces.push(s1);
+        }
+
+        if let Some(trailing) = self.trailing {
+            if !elems_empty {
+                doc = doc.append(separator);
+            }
+            spaces.push(trailing);
+        }
+        match comments(allocator, spaces, true, false) {
+            Some(comments) => doc.append(comments),
+            None => doc,
+        }
+    }
+
     pub fn pretty<'a, D, FE>(
         self,
         allocator: &'a D,
@@ -24,15 +225,7 @@ impl<E> BoundedSeparated<E> {
         D::Doc: Clone,
         FE: Fn(E) -> DocBuilder<'a, D>,
     {
-        let elems_empty = self.elems.is_empty();
-        allocator
-            .intersperse(
-                self.elems
-                    .into_iter()
-                    .map(|(s0, elem, s1)| allocator.line().append(elem_pretty(elem))),
-                separator.clone(),
-            )
-            .append(self.trailing.filter(|_| !elems_empty).map(|s| separator))
+        self.pretty_elems(allocator, separator, elem_pretty)
             .nest(NEST_DEPTH)
             .append(allocator.line())
             .enclose(start, end)
@@ -2,6 +2,8 @@ use pretty::{DocAllocator, DocBuilder, Pretty};
 
 use crate::ast::Call;
 
+use super::basic::delimited;
+
 impl<'a, D> Pretty<'a, D> for Call
 where
     D: DocAllocator<'a>,
@@ -17,20 +19,33 @@ where
                 s2,
                 span: _,
             } => expr
-                .pretty(allocator)
-                .append(arg.pretty(allocator).parens()),
+                .pretty_base(allocator)
+                .append(s0.pretty_or(allocator, allocator.nil()))
+                .append(delimited(
+                    allocator,
+                    "(",
+                    s1,
+                    Some((arg.pretty(allocator), s2)),
+                    ")",
+                )),
             Self::NoArg {
                 expr,
                 s0,
                 s1,
                 span: _,
-            } => expr.pretty(allocator).append(allocator.nil().parens()),
+            } => expr
+                .pretty_base(allocator)
+                .append(s0.pretty_or(allocator, allocator.nil()))
+                .append(delimited(allocator, "(", s1, None, ")")),
             Self::Constr {
                 expr,
                 s0,
                 constr,
                 span: _,
-            } => expr.pretty(allocator).append(constr.pretty(allocator)),
+            } => expr
+                .pretty_base(allocator)
+                .append(s0.pretty_or(allocator, allocator.nil()))
+                .append(constr.pretty(allocator)),
         }
     }
 }
@@ -1,24 +1,175 @@
 use pretty::{DocAllocator, DocBuilder, Pretty};
 
-use crate::ast::{Assoc, BinOp, Expr, Field, Var};
+use crate::ast::{Assoc, BinOp, Expr, Field, Space, Var};
+
+use super::basic::{comments, delimited};
+use super::NEST_DEPTH;
 
 impl<'a, D: DocAllocator<'a>> Pretty<'a, D> for BinOp {
     fn pretty(self, allocator: &'a D) -> DocBuilder<'a, D> {
-        allocator.text(match self {
-            Self::Mul => "*",
-            Self::Div => "/",
-            Self::Mod => "%",
-            Self::Add => "+",
-            Self::Sub => "-",
-            Self::Eq => "==",
-            Self::Neq => "!=",
-            Self::Gt => ">",
-            Self::Ge => ">=",
-            Self::Lt => "<",
-            Self::Le => "<=",
-            Self::And => "and",
-            Self::Or => "or",
-        })
+        allocator.text(self.symbol())
+    }
+}
+
+impl BinOp {
+    /// Whether an expression must be parenthesized as left operand.
+    fn left_needs_parens(self, left: &Expr) -> bool {
+        // If we're left-associative, then the left subexpression can be at the
+        // same precedence. If we're right-associative, it must be at a higher
+        // precedence.
+        let min_left_prec = match self.assoc() {
+            Assoc::Left => self.precedence(),
+            Assoc::Right => self.precedence() + 1,
+        };
+
+        match left {
+            Expr::BinOp { op, .. } if op.precedence() < min_left_prec => true,
+            // If we don't add parentheses, we'll be assimilated into the
+            // expression at the end of the left subexpression.
+            _ => left.ends_in_expr(),
+        }
+    }
+
+    /// Whether an expression must be parenthesized as right operand.
+    fn right_needs_parens(self, right: &Expr) -> bool {
+        // If we're left-associative, then the right subexpression must be at a
+        // higher precedence. If we're right-associative, it can be at the same
+        // precedence.
+        let min_right_prec = match self.assoc() {
+            Assoc::Left => self.precedence() + 1,
+            Assoc::Right => self.precedence(),
+        };
+
+        matches!(right, Expr::BinOp { op, .. } if op.precedence() < min_right_prec)
+    }
+}
+
+/// An operand of a binary operation and whether it needs parentheses.
+type Operand = (Expr, bool);
+
+impl Expr {
+    /// Whether the expression ends with an arbitrary expression on the right,
+    /// which would swallow any operators following it.
+    fn ends_in_expr(&self) -> bool {
+        match self {
+            Self::Field(Field::Assign { .. } | Field::AssignIdent { .. }) => true,
+            Self::Var(Var::Assign { .. } | Var::AssignIdent { .. }) => true,
+            Self::TableDestr(_) | Self::FuncDef(_) => true,
+            Self::Neg { expr, .. } | Self::Not { expr, .. } => {
+                !matches!(**expr, Self::BinOp { .. }) && expr.ends_in_expr()
+            }
+            Self::BinOp { op, right, .. } => !op.right_needs_parens(right) && right.ends_in_expr(),
+            _ => false,
+        }
+    }
+
+    /// Split a binary operation into a chain of operands at the same
+    /// precedence, along with whether each operand needs parentheses.
+    ///
+    /// For example, `a + b - c * d` becomes `a`, `+ b` and `- c * d`.
+    fn into_chain(self) -> (Operand, Vec<(Space, BinOp, Space, Operand)>) {
+        let (left, s0, op, s1, right) = match self {
+            Self::BinOp {
+                left,
+                s0,
+                op,
+                s1,
+                right,
+                span: _,
+            } => (*left, s0, op, s1, *right),
+            expr => return ((expr, false), vec![]),
+        };
+        let continues = |expr: &Self| matches!(expr, Self::BinOp { op: inner, .. } if inner.precedence() == op.precedence());
+
+        match op.assoc() {
+            Assoc::Left => {
+                let (first, mut rest) = if continues(&left) && !op.left_needs_parens(&left) {
+                    left.into_chain()
+                } else {
+                    let paren = op.left_needs_parens(&left);
+                    ((left, paren), vec![])
+                };
+                let paren = op.right_needs_parens(&right);
+                rest.push((s0, op, s1, (right, paren)));
+                (first, rest)
+            }
+            Assoc::Right => {
+                let paren = op.left_needs_parens(&left);
+                let first = (left, paren);
+                if continues(&right) && !op.right_needs_parens(&right) {
+                    let (second, rest) = right.into_chain();
+                    let mut chain = vec![(s0, op, s1, second)];
+                    chain.extend(rest);
+                    (first, chain)
+                } else {
+                    let paren = op.right_needs_parens(&right);
+                    (first, vec![(s0, op, s1, (right, paren))])
+                }
+            }
+        }
+    }
+
+    /// Print a chain of binary operations at the same precedence as a group.
+    ///
+    /// If the chain doesn't fit on one line, it is broken after every operator
+    /// and the operands after the first are indented. Comments before an
+    /// operator move it to the start of the next line instead. Operands at a higher
+    /// precedence form groups of their own, so they are only broken if they
+    /// don't fit on a line by themselves.
+    fn pretty_bin_op<'a, D>(self, allocator: &'a D) -> DocBuilder<'a, D>
+    where
+        D: DocAllocator<'a>,
+        D::Doc: Clone,
+    {
+        let operand = |(expr, paren): Operand| {
+            let doc = expr.pretty(allocator);
+            if paren {
+                doc.parens()
+            } else {
+                doc
+            }
+        };
+
+        let (first, rest) = self.into_chain();
+        let mut tail = allocator.nil();
+        for (s0, op, s1, expr) in rest {
+            // After comments, the operator starts a new line and the operand
+            // stays on that line with it.
+            let (before, after) = match comments(allocator, vec![s0], true, true) {
+                Some(comments) => (comments.append(allocator.hardline()), allocator.space()),
+                None => (allocator.space(), allocator.line()),
+            };
+            tail = tail
+                .append(before)
+                .append(op.pretty(allocator))
+                .append(s1.pretty_or(allocator, after))
+                .append(operand(expr));
+        }
+        operand(first).append(tail.nest(NEST_DEPTH)).group()
+    }
+
+    /// Print an expression that is followed by a call or field access,
+    /// parenthesizing it unless the suffix can be attached directly.
+    pub fn pretty_base<'a, D>(self, allocator: &'a D) -> DocBuilder<'a, D>
+    where
+        D: DocAllocator<'a>,
+        D::Doc: Clone,
+    {
+        let parenthesize = !matches!(
+            self,
+            Self::Lit(_)
+                | Self::Call(_)
+                | Self::Field(Field::Access { .. } | Field::AccessIdent { .. })
+                | Self::Var(Var::Access { .. } | Var::AccessIdent(_))
+                | Self::TableConstr(_)
+                | Self::Paren { .. }
+        );
+        let doc = self.pretty(allocator);
+        if parenthesize {
+            doc.parens()
+        } else {
+            doc
+        }
     }
 }
 
@@ -41,7 +192,7 @@ where
                 inner,
                 s1,
                 span: _,
-            } => inner.pretty(allocator).parens(),
+            } => delimited(allocator, "(", s0, Some((inner.pretty(allocator), s1)), ")"),
 
             Self::Neg {
                 minus: _,
@@ -53,6 +204,7 @@ where
                 let inner = expr.pretty(allocator);
                 allocator
                     .text("-")
+                    .append(s0.pretty_or(allocator, allocator.nil()))
                     .append(if parenthesize { inner.parens() } else { inner })
             }
 
@@ -65,67 +217,14 @@ where
                 let parenthesize = matches!(*expr, Self::BinOp { .. });
                 let inner = expr.pretty(allocator);
                 allocator
-                    .text("not ")
+                    .text("not")
+                    .append(s0.pretty_or(allocator, allocator.space()))
                     .append(if parenthesize { inner.parens() } else { inner })
             }
 
-            // TODO Add newlines and group properly
-            Self::BinOp {
-                left,
-                s0,
-                op,
-                s1,
-                right,
-                span: _,
-            } => {
-                // If we're left-associative, then the left subexpression can be
-                // at the same precedence and the right subexpression must be at
-                // a higher precedence.
-
-                // If we're right-associative, then the left subexpression must
-                // be at a higher precedence and the right subexpression can be
-                // at the same precedence.
-
-                // Minimum precedence that the left subexpression can be at
-                // without requiring parentheses.
-                let min_left_prec = match op.assoc() {
-                    Assoc::Left => op.precedence(),
-                    Assoc::Right => op.precedence() + 1,
-                };
-
-                // Minimum precedence that the right subexpression can be at
-                // without requiring parentheses.
-                let min_right_prec = match op.assoc() {
-                    Assoc::Left => op.precedence() + 1,
-                    Assoc::Right => op.precedence(),
-                };
-
-                let left_paren = match *left {
-                    // These end with an arbitrary expression on the right. If
-                    // we don't add parentheses, we'll be assimilated into that
-                    // expression.
-                    Self::Field(Field::Assign { .. } | Field::AssignIdent { .. }) => true,
-                    Self::Var(Var::Assign { .. } | Var::AssignIdent { .. }) => true,
-
-                    Self::BinOp { op, .. } if op.precedence() < min_left_prec => true,
+            Self::BinOp { .. } => self.pretty_bin_op(allocator),
 
-                    _ => false,
-                };
-
-                let right_paren =
-                    matches!(*right, Self::BinOp { op, .. } if op.precedence() < min_right_prec);
-
-                let left = left.pretty(allocator);
-                let left = if left_paren { left.parens() } else { left };
-
-                let right = right.pretty(allocator);
-                let right = if right_paren { right.parens() } else { right };
-
-                left.append(allocator.space())
-                    .append(op.pretty(allocator))
-                    .append(allocator.space())
-                    .append(right)
-            }
+            Self::Error(_) => allocator.text("<error>"),
         }
     }
 }
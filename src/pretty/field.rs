@@ -1,6 +1,30 @@
 use pretty::{DocAllocator, DocBuilder, Pretty};
 
-use crate::ast::Field;
+use crate::ast::{Field, Ident, Space};
+
+use super::basic::{comments, delimited};
+use super::NEST_DEPTH;
+
+/// Print `s0 . s1 ident`, breaking before the `.` if the expression doesn't fit
+/// on one line. Comments indent the code after them.
+fn dot_ident<'a, D>(allocator: &'a D, s0: Space, s1: Space, ident: Ident) -> DocBuilder<'a, D>
+where
+    D: DocAllocator<'a>,
+    D::Doc: Clone,
+{
+    // Comments after the `.` keep it on the line of the expression before.
+    let (sep, ident) = match comments(allocator, vec![s1], true, true) {
+        Some(comments) => (
+            allocator.nil(),
+            comments
+                .append(allocator.hardline())
+                .append(ident.pretty(allocator))
+                .nest(NEST_DEPTH),
+        ),
+        None => (allocator.line_(), ident.pretty(allocator)),
+    };
+    s0.pretty_then(allocator, sep, allocator.text(".").append(ident))
+}
 
 impl<'a, D> Pretty<'a, D> for Field
 where
@@ -17,8 +41,15 @@ where
                 s2,
                 span: _,
             } => expr
-                .pretty(allocator)
-                .append(index.pretty(allocator).brackets()),
+                .pretty_base(allocator)
+                .append(s0.pretty_or(allocator, allocator.nil()))
+                .append(delimited(
+                    allocator,
+                    "[",
+                    s1,
+                    Some((index.pretty(allocator), s2)),
+                    "]",
+                )),
             Self::Assign {
                 expr,
                 s0,
@@ -30,9 +61,18 @@ where
                 value,
                 span: _,
             } => expr
-                .pretty(allocator)
-                .append(index.pretty(allocator).brackets())
-                .append(allocator.text(" = "))
+                .pretty_base(allocator)
+                .append(s0.pretty_or(allocator, allocator.nil()))
+                .append(delimited(
+                    allocator,
+                    "[",
+                    s1,
+                    Some((index.pretty(allocator), s2)),
+                    "]",
+                ))
+                .append(s3.pretty_or(allocator, allocator.space()))
+                .append(allocator.text("="))
+                .append(s4.pretty_or(allocator, allocator.space()))
                 .append(value.pretty(allocator)),
             Self::AccessIdent {
                 expr,
@@ -41,10 +81,8 @@ where
                 ident,
                 span: _,
             } => expr
-                .pretty(allocator)
-                .append(allocator.line_())
-                .append(allocator.text("."))
-                .append(ident.pretty(allocator))
+                .pretty_base(allocator)
+                .append(dot_ident(allocator, s0, s1, ident))
                 .group(),
             Self::AssignIdent {
                 expr,
@@ -56,11 +94,11 @@ where
                 value,
                 span: _,
             } => expr
-                .pretty(allocator)
-                .append(allocator.line_())
-                .append(allocator.text("."))
-                .append(ident.pretty(allocator))
-                .append(allocator.text(" = "))
+                .pretty_base(allocator)
+                .append(dot_ident(allocator, s0, s1, ident))
+                .append(s2.pretty_or(allocator, allocator.space()))
+                .append(allocator.text("="))
+                .append(s3.pretty_or(allocator, allocator.space()))
                 .append(value.pretty(allocator))
                 .group(),
         }
@@ -2,6 +2,8 @@ use pretty::{DocAllocator, DocBuilder, Pretty};
 
 use crate::ast::FuncDef;
 
+use super::basic::{delimited, local};
+
 impl<'a, D> Pretty<'a, D> for FuncDef
 where
     D: DocAllocator<'a>,
@@ -15,7 +17,11 @@ where
                 s2,
                 body,
                 span: _,
-            } => allocator.text("function() ").append(body.pretty(allocator)),
+            } => allocator
+                .text("function")
+                .append(s0.pretty_or(allocator, allocator.nil()))
+                .append(delimited(allocator, "(", s1, None, ")"))
+                .append(s2.pretty_then(allocator, allocator.space(), body.pretty(allocator))),
 
             Self::AnonArg {
                 s0,
@@ -27,9 +33,15 @@ where
                 span: _,
             } => allocator
                 .text("function")
-                .append(arg.pretty(allocator).parens())
-                .append(allocator.space())
-                .append(body.pretty(allocator)),
+                .append(s0.pretty_or(allocator, allocator.nil()))
+                .append(delimited(
+                    allocator,
+                    "(",
+                    s1,
+                    Some((arg.pretty(allocator), s2)),
+                    ")",
+                ))
+                .append(s3.pretty_then(allocator, allocator.space(), body.pretty(allocator))),
 
             Self::AnonDestr {
                 s0,
@@ -39,12 +51,12 @@ where
                 span: _,
             } => allocator
                 .text("function")
+                .append(s0.pretty_or(allocator, allocator.nil()))
                 .append(pattern.pretty(allocator))
-                .append(allocator.space())
-                .append(body.pretty(allocator)),
+                .append(s1.pretty_then(allocator, allocator.space(), body.pretty(allocator))),
 
             Self::NamedNoArg {
-                local,
+                local: l,
                 s0,
                 name,
                 s1,
@@ -52,16 +64,16 @@ where
                 s3,
                 body,
                 span: _,
-            } => local
-                .map(|s| allocator.text("local "))
-                .unwrap_or_else(|| allocator.nil())
-                .append(allocator.text("function "))
+            } => local(allocator, l)
+                .append(allocator.text("function"))
+                .append(s0.pretty_or(allocator, allocator.space()))
                 .append(name)
-                .append(allocator.text("() "))
-                .append(body.pretty(allocator)),
+                .append(s1.pretty_or(allocator, allocator.nil()))
+                .append(delimited(allocator, "(", s2, None, ")"))
+                .append(s3.pretty_then(allocator, allocator.space(), body.pretty(allocator))),
 
             Self::NamedArg {
-                local,
+                local: l,
                 s0,
                 name,
                 s1,
@@ -71,17 +83,22 @@ where
                 s4,
                 body,
                 span: _,
-            } => local
-                .map(|s| allocator.text("local "))
-                .unwrap_or_else(|| allocator.nil())
-                .append(allocator.text("function "))
+            } => local(allocator, l)
+                .append(allocator.text("function"))
+                .append(s0.pretty_or(allocator, allocator.space()))
                 .append(name)
-                .append(arg.pretty(allocator).parens())
-                .append(allocator.space())
-                .append(body.pretty(allocator)),
+                .append(s1.pretty_or(allocator, allocator.nil()))
+                .append(delimited(
+                    allocator,
+                    "(",
+                    s2,
+                    Some((arg.pretty(allocator), s3)),
+                    ")",
+                ))
+                .append(s4.pretty_then(allocator, allocator.space(), body.pretty(allocator))),
 
             Self::NamedDestr {
-                local,
+                local: l,
                 s0,
                 name,
                 s1,
@@ -89,14 +106,13 @@ where
                 s2,
                 body,
                 span: _,
-            } => local
-                .map(|s| allocator.text("local "))
-                .unwrap_or_else(|| allocator.nil())
-                .append(allocator.text("function "))
+            } => local(allocator, l)
+                .append(allocator.text("function"))
+                .append(s0.pretty_or(allocator, allocator.space()))
                 .append(name)
+                .append(s1.pretty_or(allocator, allocator.nil()))
                 .append(pattern.pretty(allocator))
-                .append(allocator.space())
-                .append(body.pretty(allocator)),
+                .append(s2.pretty_then(allocator, allocator.space(), body.pretty(allocator))),
         }
     }
 }
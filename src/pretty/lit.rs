@@ -1,6 +1,6 @@
 use pretty::{DocAllocator, DocBuilder, Pretty};
 
-use crate::ast::{Lit, NumLit, StringLit, StringLitElem, TableLit, TableLitElem};
+use crate::ast::{FloatLit, Lit, NumLit, StringLit, StringLitElem, TableLit, TableLitElem};
 
 impl<'a, D: DocAllocator<'a>> Pretty<'a, D> for NumLit {
     fn pretty(self, allocator: &'a D) -> DocBuilder<'a, D> {
@@ -8,6 +8,12 @@ impl<'a, D: DocAllocator<'a>> Pretty<'a, D> for NumLit {
     }
 }
 
+impl<'a, D: DocAllocator<'a>> Pretty<'a, D> for FloatLit {
+    fn pretty(self, allocator: &'a D) -> DocBuilder<'a, D> {
+        allocator.text(self.str)
+    }
+}
+
 impl<'a, D: DocAllocator<'a>> Pretty<'a, D> for StringLitElem {
     fn pretty(self, allocator: &'a D) -> DocBuilder<'a, D> {
         match self {
@@ -46,7 +52,9 @@ where
                 span: _,
             } => name
                 .pretty(allocator)
-                .append(allocator.text(": "))
+                .append(s0.pretty_or(allocator, allocator.nil()))
+                .append(allocator.text(":"))
+                .append(s1.pretty_or(allocator, allocator.space()))
                 .append(value.pretty(allocator)),
         }
     }
@@ -80,6 +88,7 @@ where
             Self::Bool(true, _) => allocator.text("true"),
             Self::Builtin(builtin, _) => allocator.text(format!("{builtin:?}")),
             Self::Num(num) => num.pretty(allocator),
+            Self::Float(float) => float.pretty(allocator),
             Self::String(string) => string.pretty(allocator),
             Self::Table(table) => table.pretty(allocator),
         }
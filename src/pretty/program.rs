@@ -1,6 +1,10 @@
+use std::mem;
+
 use pretty::{DocAllocator, DocBuilder, Pretty};
 
-use crate::ast::Program;
+use crate::ast::{Program, Space};
+
+use super::basic::comments;
 
 impl<'a, D> Pretty<'a, D> for Program
 where
@@ -8,26 +12,44 @@ where
     D::Doc: Clone,
 {
     fn pretty(self, allocator: &'a D) -> DocBuilder<'a, D> {
-        match self {
+        let (s0, doc, s1) = match self {
             Self::Expr {
                 s0,
                 expr,
                 s1,
                 span: _,
-            } => expr.pretty(allocator),
+            } => (s0, expr.pretty(allocator), s1),
 
-            Self::Module { s0, elems, span: _ } => {
-                allocator.text("module").append(allocator.line()).append(
-                    allocator
-                        .intersperse(
-                            elems.elems.into_iter().map(|(s0, elem, s1)| {
-                                allocator.line().append(elem.pretty(allocator))
-                            }),
-                            allocator.text(","),
-                        )
-                        .append(elems.trailing.map(|s| allocator.text(","))),
-                )
+            Self::Module {
+                s0,
+                mut elems,
+                span,
+            } => {
+                // Comments directly after `module` stay on its line.
+                let first = match elems.elems.first_mut() {
+                    Some((s0, _, _)) => Some(s0),
+                    None => elems.trailing.as_mut(),
+                };
+                let after_module = first
+                    .map(|s| mem::replace(s, Space::empty(span)))
+                    .and_then(|s| comments(allocator, vec![s], true, false))
+                    .unwrap_or_else(|| allocator.nil());
+                let elems =
+                    elems.pretty_elems(allocator, allocator.text(","), |e| e.pretty(allocator));
+                let doc = allocator
+                    .text("module")
+                    .append(after_module)
+                    .append(allocator.line())
+                    .append(elems);
+                (s0, doc, Space::empty(span))
             }
-        }
+        };
+
+        let leading = comments(allocator, vec![s0], false, true)
+            .map(|c| c.append(allocator.hardline()))
+            .unwrap_or_else(|| allocator.nil());
+        let trailing =
+            comments(allocator, vec![s1], true, false).unwrap_or_else(|| allocator.nil());
+        leading.append(doc).append(trailing)
     }
 }
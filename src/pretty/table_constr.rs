@@ -2,6 +2,8 @@ use pretty::{DocAllocator, DocBuilder, Pretty};
 
 use crate::ast::{TableConstr, TableConstrElem};
 
+use super::basic::delimited;
+
 impl<'a, D> Pretty<'a, D> for TableConstrElem
 where
     D: DocAllocator<'a>,
@@ -18,10 +20,10 @@ where
                 s3,
                 value,
                 span: _,
-            } => index
-                .pretty(allocator)
-                .brackets()
-                .append(allocator.text(": "))
+            } => delimited(allocator, "[", s0, Some((index.pretty(allocator), s1)), "]")
+                .append(s2.pretty_or(allocator, allocator.nil()))
+                .append(allocator.text(":"))
+                .append(s3.pretty_or(allocator, allocator.space()))
                 .append(value.pretty(allocator)),
         }
     }
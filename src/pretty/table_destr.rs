@@ -1,21 +1,77 @@
 use pretty::{DocAllocator, DocBuilder, Pretty};
 
-use crate::ast::{TableDestr, TablePattern, TablePatternElem};
+use crate::ast::{Pattern, TableDestr, TablePattern, TablePatternElem};
 
-impl<'a, D: DocAllocator<'a>> Pretty<'a, D> for TablePatternElem {
+use super::basic::local;
+
+impl<'a, D> Pretty<'a, D> for Pattern
+where
+    D: DocAllocator<'a>,
+    D::Doc: Clone,
+{
+    fn pretty(self, allocator: &'a D) -> DocBuilder<'a, D> {
+        match self {
+            Self::Ident(ident) => ident.pretty(allocator),
+            Self::Table(pattern) => pattern.pretty(allocator),
+        }
+    }
+}
+
+impl<'a, D> Pretty<'a, D> for TablePatternElem
+where
+    D: DocAllocator<'a>,
+    D::Doc: Clone,
+{
     fn pretty(self, allocator: &'a D) -> DocBuilder<'a, D> {
         match self {
-            Self::Positional(ident) => ident.pretty(allocator),
+            Self::Positional(pattern) => pattern.pretty(allocator),
+            Self::PositionalDefault {
+                pattern,
+                s0,
+                s1,
+                default,
+                span: _,
+            } => pattern
+                .pretty(allocator)
+                .append(s0.pretty_or(allocator, allocator.space()))
+                .append(allocator.text("="))
+                .append(s1.pretty_or(allocator, allocator.space()))
+                .append(default.pretty(allocator)),
             Self::Named {
                 name,
                 s0,
                 s1,
-                ident,
+                pattern,
                 span: _,
             } => name
                 .pretty(allocator)
-                .append(allocator.text(": "))
-                .append(ident.pretty(allocator)),
+                .append(s0.pretty_or(allocator, allocator.nil()))
+                .append(allocator.text(":"))
+                .append(s1.pretty_or(allocator, allocator.space()))
+                .append(pattern.pretty(allocator)),
+            Self::NamedDefault {
+                name,
+                s0,
+                s1,
+                pattern,
+                s2,
+                s3,
+                default,
+                span: _,
+            } => name
+                .pretty(allocator)
+                .append(s0.pretty_or(allocator, allocator.nil()))
+                .append(allocator.text(":"))
+                .append(s1.pretty_or(allocator, allocator.space()))
+                .append(pattern.pretty(allocator))
+                .append(s2.pretty_or(allocator, allocator.space()))
+                .append(allocator.text("="))
+                .append(s3.pretty_or(allocator, allocator.space()))
+                .append(default.pretty(allocator)),
+            Self::Rest { s0, name, span: _ } => allocator
+                .text("..")
+                .append(s0.pretty_or(allocator, allocator.nil()))
+                .append(name.pretty(allocator)),
         }
     }
 }
@@ -42,12 +98,20 @@ where
     D::Doc: Clone,
 {
     fn pretty(self, allocator: &'a D) -> DocBuilder<'a, D> {
-        // TODO Handle spaces
-        self.local
-            .map(|s| allocator.text("local "))
-            .unwrap_or_else(|| allocator.nil())
-            .append(self.pattern.pretty(allocator))
-            .append(allocator.text(" = "))
-            .append(self.value.pretty(allocator))
+        let Self {
+            local: l,
+            pattern,
+            s0,
+            s1,
+            value,
+            span: _,
+        } = self;
+
+        local(allocator, l)
+            .append(pattern.pretty(allocator))
+            .append(s0.pretty_or(allocator, allocator.space()))
+            .append(allocator.text("="))
+            .append(s1.pretty_or(allocator, allocator.space()))
+            .append(value.pretty(allocator))
     }
 }
@@ -2,6 +2,8 @@ use pretty::{DocAllocator, DocBuilder, Pretty};
 
 use crate::ast::Var;
 
+use super::basic::{delimited, local};
+
 impl<'a, D> Pretty<'a, D> for Var
 where
     D: DocAllocator<'a>,
@@ -14,9 +16,9 @@ where
                 index,
                 s1,
                 span: _,
-            } => index.pretty(allocator).brackets(),
+            } => delimited(allocator, "[", s0, Some((index.pretty(allocator), s1)), "]"),
             Self::Assign {
-                local,
+                local: l,
                 s0,
                 index,
                 s1,
@@ -24,25 +26,31 @@ where
                 s3,
                 value,
                 span: _,
-            } => local
-                .map(|s| allocator.text("local "))
-                .unwrap_or_else(|| allocator.nil())
-                .append(index.pretty(allocator).brackets())
-                .append(allocator.text(" = "))
+            } => local(allocator, l)
+                .append(delimited(
+                    allocator,
+                    "[",
+                    s0,
+                    Some((index.pretty(allocator), s1)),
+                    "]",
+                ))
+                .append(s2.pretty_or(allocator, allocator.space()))
+                .append(allocator.text("="))
+                .append(s3.pretty_or(allocator, allocator.space()))
                 .append(value.pretty(allocator)),
             Self::AccessIdent(ident) => ident.pretty(allocator),
             Self::AssignIdent {
-                local,
+                local: l,
                 name,
                 s0,
                 s1,
                 value,
                 span: _,
-            } => local
-                .map(|s| allocator.text("local "))
-                .unwrap_or_else(|| allocator.nil())
+            } => local(allocator, l)
                 .append(name.pretty(allocator))
-                .append(allocator.text(" = "))
+                .append(s0.pretty_or(allocator, allocator.space()))
+                .append(allocator.text("="))
+                .append(s1.pretty_or(allocator, allocator.space()))
                 .append(value.pretty(allocator)),
         }
     }
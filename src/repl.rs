@@ -0,0 +1,188 @@
+//! Support for `tada repl`.
+//!
+//! All entries share one [`Runtime`] and thus one top-level scope, so variables
+//! assigned in one entry are visible in the next. The entries are also
+//! collected into a single [`SourceFile`] so that errors in a function defined
+//! by an earlier entry still point to the right code.
+
+use std::env;
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use tada::ast::{Expr, Field, FuncDef, Program, Var};
+use tada::desugar::Kind;
+use tada::diagnostic::Diagnostic;
+use tada::eval::Runtime;
+use tada::pretty;
+use tada::source::SourceFile;
+use tada::value::Value;
+
+use crate::{fmt, parse_partial_at, report, runtime_error};
+
+const HELP: &str = "\
+Enter an expression to evaluate it. Entries with unclosed (, [, { or '{
+continue on the next line.
+
+:desugar <expr>  Show the fully desugared expression
+:pretty <expr>   Show the pretty-printed expression
+:help            Show this message
+:quit            Exit the repl";
+
+/// Whether `text` contains unclosed delimiters, meaning it continues on the
+/// next line.
+///
+/// Mismatched delimiters don't count, since more input won't fix those.
+fn is_incomplete(text: &str) -> bool {
+    let mut open = vec![];
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '#' => {
+                chars.by_ref().find(|c| *c == '\n');
+            }
+            '"' => loop {
+                match chars.next() {
+                    Some('\\') => {
+                        chars.next();
+                    }
+                    Some('"') => break,
+                    Some(_) => {}
+                    None => return false,
+                }
+            },
+            '(' => open.push(')'),
+            '[' => open.push(']'),
+            '{' => open.push('}'),
+            ')' | ']' | '}' if open.pop() != Some(c) => return false,
+            _ => {}
+        }
+    }
+    !open.is_empty()
+}
+
+/// Whether a program is an assignment or a named function definition. Their
+/// value is whatever table they assigned to, which is usually the scope and
+/// not worth printing.
+fn is_assignment(program: &Program) -> bool {
+    let Program::Expr { expr, .. } = program else {
+        return false;
+    };
+    matches!(
+        **expr,
+        Expr::Var(Var::Assign { .. } | Var::AssignIdent { .. })
+            | Expr::Field(Field::Assign { .. } | Field::AssignIdent { .. })
+            | Expr::TableDestr(_)
+            | Expr::FuncDef(
+                FuncDef::NamedNoArg { .. } | FuncDef::NamedArg { .. } | FuncDef::NamedDestr { .. }
+            )
+    )
+}
+
+struct Repl {
+    runtime: Runtime,
+    /// The text of all entries so far.
+    history: String,
+    width: usize,
+    prompt: bool,
+}
+
+impl Repl {
+    /// Read lines until the entry is complete. Returns `None` at the end of
+    /// the input.
+    fn read_entry(&self, input: &mut impl BufRead) -> anyhow::Result<Option<String>> {
+        let mut entry = String::new();
+        loop {
+            if self.prompt {
+                print!("{}", if entry.is_empty() { "> " } else { ".. " });
+                io::stdout().flush()?;
+            }
+            if input.read_line(&mut entry)? == 0 {
+                return Ok((!entry.is_empty()).then_some(entry));
+            }
+            if !entry.ends_with('\n') {
+                entry.push('\n');
+            }
+            if !is_incomplete(&entry) {
+                return Ok(Some(entry));
+            }
+        }
+    }
+
+    /// Parse code starting at character `offset` of the history and handle the
+    /// result, or report the parse errors.
+    fn with_program(
+        &mut self,
+        code: &str,
+        offset: usize,
+        f: impl FnOnce(&mut Self, &SourceFile, Program) -> Result<(), Diagnostic>,
+    ) {
+        let file = SourceFile::new("<repl>".to_string(), self.history.clone());
+        let result = match parse_partial_at(code, offset) {
+            (Some(program), diagnostics) if diagnostics.is_empty() => f(self, &file, program),
+            (_, diagnostics) => return report(&file, diagnostics),
+        };
+        if let Err(diagnostic) = result {
+            report(&file, [diagnostic]);
+        }
+    }
+
+    /// Handle an entry. Returns `false` if the repl should exit.
+    fn entry(&mut self, entry: &str) -> bool {
+        let offset = self.history.chars().count();
+        self.history.push_str(entry);
+
+        let Some(command) = entry.trim_start().strip_prefix(':') else {
+            if !entry.trim().is_empty() {
+                self.with_program(entry, offset, |this, file, program| {
+                    let assignment = is_assignment(&program);
+                    let (program, provenance) = program.desugar_fully(&Kind::all());
+                    let value = this
+                        .runtime
+                        .run(program)
+                        .map_err(|err| runtime_error(file, &provenance, err))?;
+                    if !assignment && !matches!(value, Value::Nil) {
+                        println!("{value}");
+                    }
+                    Ok(())
+                });
+            }
+            return true;
+        };
+
+        let (name, code) = command
+            .split_once(char::is_whitespace)
+            .unwrap_or((command, ""));
+        let offset = offset + entry.chars().count() - code.chars().count();
+        match name {
+            "desugar" => self.with_program(code, offset, |this, _, program| {
+                let (program, _) = program.desugar_fully(&Kind::all());
+                print!("{}", pretty::pretty_to_string(program, this.width));
+                Ok(())
+            }),
+            "pretty" => self.with_program(code, offset, |this, _, program| {
+                print!("{}", pretty::pretty_to_string(program, this.width));
+                Ok(())
+            }),
+            "help" => println!("{HELP}"),
+            "quit" => return false,
+            name => eprintln!("unknown command :{name}, try :help"),
+        }
+        true
+    }
+}
+
+pub fn repl() -> anyhow::Result<()> {
+    let mut repl = Repl {
+        runtime: Runtime::new(),
+        history: String::new(),
+        width: fmt::Config::find(&env::current_dir()?)?.width,
+        prompt: io::stdin().is_terminal(),
+    };
+
+    let mut input = io::stdin().lock();
+    while let Some(entry) = repl.read_entry(&mut input)? {
+        if !repl.entry(&entry) {
+            break;
+        }
+    }
+    Ok(())
+}
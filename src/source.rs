@@ -0,0 +1,168 @@
+//! Mapping between [`Span`]s and lines and columns.
+//!
+//! Spans count characters from the start of the file. Humans, editors and
+//! other tools want to know lines and columns instead, and some of them count
+//! columns in UTF-8 bytes rather than characters. A [`SourceFile`] indexes the
+//! start of every line once so these conversions don't need to scan the whole
+//! file.
+//!
+//! Lines are separated by `\n`. A `\r` directly before a `\n` belongs to the
+//! line terminator, not to the line's text.
+
+use std::fmt;
+use std::ops::Range;
+
+use crate::span::Span;
+
+/// A position in a [`SourceFile`].
+///
+/// All fields are zero-based, but positions are displayed one-based as
+/// `line:col` like most editors and compilers do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    /// Column in characters.
+    pub col: usize,
+    /// Column in UTF-8 bytes.
+    pub byte_col: usize,
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line + 1, self.col + 1)
+    }
+}
+
+/// Where a line starts, both as character and as byte offset.
+#[derive(Debug, Clone, Copy)]
+struct LineStart {
+    offset: usize,
+    byte: usize,
+}
+
+#[derive(Debug)]
+pub struct SourceFile {
+    name: String,
+    text: String,
+    lines: Vec<LineStart>,
+    /// Length of the text in characters.
+    len: usize,
+}
+
+impl SourceFile {
+    pub fn new(name: String, text: String) -> Self {
+        let mut lines = vec![LineStart { offset: 0, byte: 0 }];
+        let mut len = 0;
+        for (byte, c) in text.char_indices() {
+            len += 1;
+            if c == '\n' {
+                lines.push(LineStart {
+                    offset: len,
+                    byte: byte + 1,
+                });
+            }
+        }
+        Self {
+            name,
+            text,
+            lines,
+            len,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Byte range of a line's text, without its line terminator.
+    fn line_bytes(&self, line: usize) -> Option<Range<usize>> {
+        let start = self.lines.get(line)?.byte;
+        let end = match self.lines.get(line + 1) {
+            Some(next) => {
+                let end = next.byte - 1; // Without the '\n'
+                self.text[..end].strip_suffix('\r').map_or(end, str::len)
+            }
+            None => self.text.len(),
+        };
+        Some(start..end)
+    }
+
+    /// Text of a line without its line terminator.
+    pub fn line(&self, line: usize) -> Option<&str> {
+        self.line_bytes(line).map(|range| &self.text[range])
+    }
+
+    /// The position of a character offset. Offsets past the end of the file
+    /// are treated as the end of the file.
+    pub fn position(&self, offset: usize) -> Position {
+        let offset = offset.min(self.len);
+        let line = self.lines.partition_point(|start| start.offset <= offset) - 1;
+        let start = self.lines[line];
+        let col = offset - start.offset;
+        let byte_col = self.text[start.byte..]
+            .chars()
+            .take(col)
+            .map(char::len_utf8)
+            .sum();
+        Position {
+            line,
+            col,
+            byte_col,
+        }
+    }
+
+    /// The positions of the start and end of a span.
+    pub fn positions(&self, span: Span) -> (Position, Position) {
+        let range = span.range();
+        (self.position(range.start), self.position(range.end))
+    }
+
+    /// Byte offset of a character offset.
+    pub fn byte_offset(&self, offset: usize) -> usize {
+        let position = self.position(offset);
+        self.lines[position.line].byte + position.byte_col
+    }
+
+    /// Byte range of a span.
+    pub fn byte_range(&self, span: Span) -> Range<usize> {
+        let range = span.range();
+        self.byte_offset(range.start)..self.byte_offset(range.end)
+    }
+
+    /// The character offset at a line and character column.
+    ///
+    /// Returns `None` if the line doesn't exist or is shorter than the column.
+    /// The column directly after the line's last character is valid.
+    pub fn offset(&self, line: usize, col: usize) -> Option<usize> {
+        let len = self.line(line)?.chars().count();
+        (col <= len).then(|| self.lines[line].offset + col)
+    }
+
+    /// The character offset at a line and byte column.
+    ///
+    /// Returns `None` if the line doesn't exist, is shorter than the column or
+    /// the column is not at a character boundary.
+    pub fn offset_from_byte_col(&self, line: usize, byte_col: usize) -> Option<usize> {
+        let text = self.line(line)?;
+        if !text.is_char_boundary(byte_col) {
+            return None;
+        }
+        Some(self.lines[line].offset + text[..byte_col].chars().count())
+    }
+
+    /// The span covering a byte range, if both ends are character boundaries.
+    pub fn span_from_bytes(&self, range: Range<usize>) -> Option<Span> {
+        let offset = |byte: usize| {
+            let line = self.lines.partition_point(|start| start.byte <= byte) - 1;
+            self.offset_from_byte_col(line, byte - self.lines[line].byte)
+        };
+        if range.end > self.text.len() || range.start > range.end {
+            return None;
+        }
+        Some(Span::new(offset(range.start)?, offset(range.end)?))
+    }
+}
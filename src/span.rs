@@ -3,7 +3,7 @@ use std::ops::Range;
 
 use chumsky::Stream;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Span {
     start: usize,
     end: usize,
@@ -70,9 +70,20 @@ impl chumsky::Span for Span {
 pub fn stream_from_str<'a>(
     s: &'a str,
 ) -> Stream<'a, char, Span, Box<dyn Iterator<Item = (char, Span)> + 'a>> {
-    let len = s.chars().count();
+    stream_from_str_at(s, 0)
+}
+
+/// Like [`stream_from_str`], but for text starting at character `offset` of a
+/// larger file.
+pub fn stream_from_str_at<'a>(
+    s: &'a str,
+    offset: usize,
+) -> Stream<'a, char, Span, Box<dyn Iterator<Item = (char, Span)> + 'a>> {
+    let end = offset + s.chars().count();
     Stream::from_iter(
-        Span::new(len, len),
-        Box::new(s.chars().enumerate().map(|(i, c)| (c, Span::new(i, i + 1)))),
+        Span::new(end, end),
+        Box::new(
+            (s.chars().enumerate()).map(move |(i, c)| (c, Span::new(offset + i, offset + i + 1))),
+        ),
     )
 }
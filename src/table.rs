@@ -1,35 +1,198 @@
 use std::cell::RefCell;
-use std::collections::HashMap;
 use std::convert::TryFrom;
-use std::fmt::Debug;
+use std::fmt;
 use std::hash::{Hash, Hasher};
+use std::mem;
 use std::rc::{Rc, Weak};
+use std::vec;
 
-use crate::value::Value;
+use indexmap::IndexMap;
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum Key {
-    Bool(bool),
-    // Builtin(Builtin),
-    Int(i64),
-    String(Box<String>),
-    Table(Table),
+use crate::span::Span;
+use crate::value::{float_to_int, Value};
+
+/// A value that can be used as a table key.
+///
+/// Keys share their representation with [`Value`], so converting between the
+/// two is free. A key is a value that is neither `nil` nor `NaN`. Floats that
+/// represent an int are normalized to that int, so `t[1]` and `t[1.0]` refer to
+/// the same entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Key(Value);
+
+// Keys are passed around as often as values, see the assertion on `Value`.
+const _: () = assert!(size_of::<Key>() == 16);
+
+// The normalization rules make `Value`'s equality reflexive for keys.
+impl Eq for Key {}
+
+impl Hash for Key {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(&self.0).hash(state);
+        match &self.0 {
+            Value::Bool(b) => b.hash(state),
+            Value::Builtin(b) => b.hash(state),
+            Value::Int(i) => i.hash(state),
+            Value::String(s) => s.hash(state),
+            Value::Table(t) => t.hash(state),
+            Value::Float(f) => f.to_bits().hash(state),
+            Value::Nil => unreachable!("nil is not a valid key"),
+        }
+    }
+}
+
+impl Key {
+    pub fn value(&self) -> &Value {
+        &self.0
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self.0 {
+            Value::Int(i) => Some(i),
+            _ => None,
+        }
+    }
+}
+
+impl From<Key> for Value {
+    fn from(key: Key) -> Self {
+        key.0
+    }
+}
+
+impl TryFrom<Value> for Key {
+    /// The value that could not be used as a key.
+    type Error = Value;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Nil => Err(value),
+            Value::Float(f) if f.is_nan() => Err(value),
+            Value::Float(f) => Ok(match float_to_int(f) {
+                Some(i) => Self(Value::Int(i)),
+                None => Self(value),
+            }),
+            value => Ok(Self(value)),
+        }
+    }
+}
+
+impl From<i64> for Key {
+    fn from(i: i64) -> Self {
+        Self(Value::Int(i))
+    }
+}
+
+impl From<&str> for Key {
+    fn from(s: &str) -> Self {
+        Self(s.into())
+    }
 }
 
-pub struct TableOwner(Rc<RefCell<HashMap<Key, Value>>>);
+/// The contents of a table.
+///
+/// Tables consist of an array part and a hash part. The array part holds the
+/// values of the keys `0..n` for the largest `n` where all of those keys are
+/// present. All other entries live in the hash part.
+///
+/// Desugaring produces lots of small positional tables like `'{ call: a, arg:
+/// b }`'s argument `{ a, b }`, so keeping positional elements out of the hash
+/// part makes them cheaper to build and access.
+///
+/// With the `hash-only-tables` feature, the array part stays empty. This only
+/// exists to compare the two with `cargo bench`.
+///
+/// The hash part remembers the order in which its keys were first inserted so
+/// that iterating over a table is deterministic.
+#[derive(Default)]
+struct TableData {
+    /// Never contains `nil`.
+    array: Vec<Value>,
+    /// Never contains the key `array.len()`.
+    map: IndexMap<Key, Value>,
+    meta: Option<Table>,
+    origin: Option<Span>,
+    /// See [`Table::literal_len`].
+    literal_len: Option<usize>,
+}
+
+impl TableData {
+    /// The key's index into the array part, if it belongs there.
+    fn array_index(&self, key: &Key) -> Option<usize> {
+        let i = usize::try_from(key.as_int()?).ok()?;
+        (i < self.array.len()).then_some(i)
+    }
+
+    /// See [`Table::len`].
+    fn len(&self) -> usize {
+        if cfg!(feature = "hash-only-tables") {
+            return (0..)
+                .take_while(|&i| self.map.contains_key(&Key::from(i)))
+                .count();
+        }
+        self.array.len()
+    }
 
-impl TableOwner {
-    pub fn new() -> Self {
-        Self(Rc::new(RefCell::new(HashMap::new())))
+    fn get(&self, key: &Key) -> Option<&Value> {
+        match self.array_index(key) {
+            Some(i) => Some(&self.array[i]),
+            None => self.map.get(key),
+        }
+    }
+
+    fn insert(&mut self, key: Key, value: Value) {
+        if matches!(value, Value::Nil) {
+            self.remove(&key);
+        } else if let Some(i) = self.array_index(&key) {
+            self.array[i] = value;
+        } else if !cfg!(feature = "hash-only-tables")
+            && key.as_int() == Some(self.array.len() as i64)
+        {
+            self.array.push(value);
+            // The array part may now connect to keys from the hash part.
+            while let Some(value) = self.map.remove(&Key::from(self.array.len() as i64)) {
+                self.array.push(value);
+            }
+        } else {
+            self.map.insert(key, value);
+        }
+    }
+
+    fn remove(&mut self, key: &Key) {
+        if let Some(i) = self.array_index(key) {
+            // Everything after the removed element no longer belongs to the
+            // array part.
+            let tail = self.array.split_off(i);
+            for (j, value) in tail.into_iter().enumerate().skip(1) {
+                self.map.insert(Key::from((i + j) as i64), value);
+            }
+        } else {
+            self.map.shift_remove(key);
+        }
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct Table(Weak<RefCell<HashMap<Key, Value>>>);
+/// A reference to a table.
+///
+/// Tables are reference counted. Since they can reference each other, they may
+/// form cycles that reference counting alone never frees. To break such cycles,
+/// tables should only be created through [`Heap::new_table`], which keeps track
+/// of them for the garbage collector.
+///
+/// [`Heap::new_table`]: crate::gc::Heap::new_table
+#[derive(Clone)]
+pub struct Table(Rc<RefCell<TableData>>);
+
+impl fmt::Debug for Table {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Tables may contain themselves, so their contents are not printed.
+        write!(f, "Table({:p})", Rc::as_ptr(&self.0))
+    }
+}
 
 impl PartialEq for Table {
     fn eq(&self, other: &Self) -> bool {
-        self.0.ptr_eq(&other.0)
+        Rc::ptr_eq(&self.0, &other.0)
     }
 }
 
@@ -37,28 +200,157 @@ impl Eq for Table {}
 
 impl Hash for Table {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        self.0.as_ptr().hash(state);
+        Rc::as_ptr(&self.0).hash(state);
     }
 }
 
 impl Table {
-    pub fn new(owner: &TableOwner) -> Self {
-        Self(Rc::downgrade(&owner.0))
+    pub(crate) fn new() -> Self {
+        Self(Rc::new(RefCell::new(TableData::default())))
+    }
+
+    pub fn downgrade(&self) -> WeakTable {
+        WeakTable(Rc::downgrade(&self.0))
     }
 
+    /// Number of [`Table`]s referencing this table.
+    pub fn ref_count(&self) -> usize {
+        Rc::strong_count(&self.0)
+    }
+
+    /// Span of the source code the table was quoted or built from, if any.
+    pub fn origin(&self) -> Option<Span> {
+        self.0.borrow().origin
+    }
+
+    pub fn set_origin(&self, span: Span) {
+        self.0.borrow_mut().origin = Some(span);
+    }
+
+    /// Number of positional elements of the literal the table was quoted from,
+    /// if any. Unlike [`Self::len`], this counts positional `nil`s, which
+    /// aren't stored in the table.
+    pub fn literal_len(&self) -> Option<usize> {
+        self.0.borrow().literal_len
+    }
+
+    pub fn set_literal_len(&self, len: usize) {
+        self.0.borrow_mut().literal_len = Some(len);
+    }
+
+    /// The table's metatable, which customizes how some builtins treat the
+    /// table (see [`crate::eval`]).
+    pub fn meta(&self) -> Option<Self> {
+        self.0.borrow().meta.clone()
+    }
+
+    pub fn set_meta(&self, meta: Option<Self>) {
+        // The old metatable is dropped only after the borrow ends, see
+        // `Self::clear`.
+        let old = mem::replace(&mut self.0.borrow_mut().meta, meta);
+        drop(old);
+    }
+
+    pub fn get(&self, key: &Key) -> Option<Value> {
+        self.0.borrow().get(key).cloned()
+    }
+
+    pub fn contains(&self, key: &Key) -> bool {
+        self.0.borrow().get(key).is_some()
+    }
+
+    /// The number of positional elements.
+    ///
+    /// This is the largest `n` such that all keys `0..n` are present in the
+    /// table. Positional elements after a missing key are not counted.
+    pub fn len(&self) -> usize {
+        self.0.borrow().len()
+    }
+
+    /// The number of entries, positional or not.
+    pub fn entry_count(&self) -> usize {
+        let data = self.0.borrow();
+        data.array.len() + data.map.len()
+    }
+
+    /// Whether the table has no entries at all, positional or not.
+    pub fn is_empty(&self) -> bool {
+        let data = self.0.borrow();
+        data.array.is_empty() && data.map.is_empty()
+    }
+
+    /// Iterate over all entries of the table.
+    ///
+    /// The positional elements (see [`Self::len`]) come first, in order. They
+    /// are followed by all other entries in the order their keys were first
+    /// inserted. Removing a key and inserting it again moves it to the end.
+    ///
+    /// The iterator works on a snapshot of the table, so the table may be
+    /// modified while iterating over it.
+    pub fn iter(&self) -> vec::IntoIter<(Key, Value)> {
+        let data = self.0.borrow();
+        let positional = data
+            .array
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (Key::from(i as i64), v.clone()));
+        let other = data.map.iter().map(|(k, v)| (k.clone(), v.clone()));
+        positional.chain(other).collect::<Vec<_>>().into_iter()
+    }
+
+    /// The keys of all entries, in the same order as [`Self::iter`].
+    pub fn keys(&self) -> impl Iterator<Item = Key> {
+        self.iter().map(|(k, _)| k)
+    }
+
+    /// The positional elements (see [`Self::len`]), in order.
+    pub fn positional(&self) -> vec::IntoIter<Value> {
+        let data = self.0.borrow();
+        if cfg!(feature = "hash-only-tables") {
+            let values = (0..data.len()).map(|i| data.map[&Key::from(i as i64)].clone());
+            return values.collect::<Vec<_>>().into_iter();
+        }
+        data.array.clone().into_iter()
+    }
+
+    /// Call `f` with every table that this table references directly.
+    pub fn for_each_child(&self, mut f: impl FnMut(&Self)) {
+        let data = self.0.borrow();
+        let keys = data.map.keys().map(Key::value);
+        let values = data.array.iter().chain(data.map.values());
+        for value in keys.chain(values) {
+            if let Value::Table(t) = value {
+                f(t);
+            }
+        }
+        if let Some(meta) = &data.meta {
+            f(meta);
+        }
+    }
+
+    /// Insert a value into the table. Inserting `nil` removes the key instead.
     pub fn insert(&self, key: Key, value: Value) {
-        self.0
-            .upgrade()
-            .expect("table owner was deallocated")
-            .borrow_mut()
-            .insert(key, value);
-    }
-
-    pub fn remove(&self, key: &Key) {
-        self.0
-            .upgrade()
-            .expect("table owner was deallocated")
-            .borrow_mut()
-            .remove(key);
+        self.0.borrow_mut().insert(key, value);
+    }
+
+    /// Remove all entries and the metatable from the table.
+    pub fn clear(&self) {
+        // The entries are dropped only after the borrow ends. Dropping them may
+        // free other tables, which in turn drop their entries.
+        let mut data = self.0.borrow_mut();
+        let array = mem::take(&mut data.array);
+        let map = mem::take(&mut data.map);
+        let meta = data.meta.take();
+        drop(data);
+        drop((array, map, meta));
+    }
+}
+
+/// A reference to a table that doesn't keep it alive.
+pub struct WeakTable(Weak<RefCell<TableData>>);
+
+impl WeakTable {
+    pub fn upgrade(&self) -> Option<Table> {
+        self.0.upgrade().map(Table)
     }
 }
@@ -1,16 +1,212 @@
-use std::fmt::Debug;
+use std::collections::HashSet;
+use std::fmt::{self, Debug};
+use std::rc::Rc;
 
+use crate::builtin::Builtin;
 use crate::table::Table;
 
-// TODO: Unwrap the Key part so this is only 16, not 24 bytes
-#[derive(Debug, Clone, PartialEq)]
+/// A runtime value.
+///
+/// # Equality
+///
+/// Ints and floats are compared by their numeric value, so `1 == 1.0`. Floats
+/// follow IEEE 754, meaning that `NaN` is not equal to anything, including
+/// itself. Tables are compared by identity. All other values are equal if they
+/// have the same type and contents.
+///
+/// Values are not hashable themselves. To use a value as a table key, it must
+/// be converted into a [`crate::table::Key`], which normalizes floats with an
+/// integer value to ints and rejects `nil` and `NaN`.
+#[derive(Debug, Clone)]
 pub enum Value {
-    // Bool(bool),
-    // Builtin(Builtin),
+    Bool(bool),
+    Builtin(Builtin),
     Int(i64),
-    // String(Box<String>),
+    /// Strings are immutable, so clones can share them. `Rc<str>` would be a
+    /// fat pointer and make values 24 instead of 16 bytes large.
+    String(Rc<String>),
     Table(Table),
-    // Float(f64),
-    // Nil,
+    Float(f64),
+    Nil,
     // Path(Table),
 }
+
+// Tables, scopes and call arguments are all made of values, so they are copied
+// around a lot. Every variant must fit into 8 bytes next to the tag.
+const _: () = assert!(size_of::<Value>() == 16);
+
+/// Convert a float to an int if it represents exactly that int.
+pub fn float_to_int(f: f64) -> Option<i64> {
+    // -2^63 and 2^63 can be represented exactly as floats. The range is
+    // exclusive at the top because i64::MAX is not representable as a float.
+    const MIN: f64 = -9_223_372_036_854_775_808.0;
+    const MAX: f64 = 9_223_372_036_854_775_808.0;
+    if f.fract() == 0.0 && (MIN..MAX).contains(&f) {
+        Some(f as i64)
+    } else {
+        None
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Bool(a), Self::Bool(b)) => a == b,
+            (Self::Builtin(a), Self::Builtin(b)) => a == b,
+            (Self::Int(a), Self::Int(b)) => a == b,
+            (Self::String(a), Self::String(b)) => a == b,
+            (Self::Table(a), Self::Table(b)) => a == b,
+            (Self::Float(a), Self::Float(b)) => a == b,
+            (Self::Int(i), Self::Float(f)) | (Self::Float(f), Self::Int(i)) => {
+                float_to_int(*f) == Some(*i)
+            }
+            (Self::Nil, Self::Nil) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Value {
+    /// Whether the value counts as true in conditions.
+    ///
+    /// Only `nil` and `false` are falsy, every other value is truthy.
+    pub fn truthy(&self) -> bool {
+        !matches!(self, Self::Nil | Self::Bool(false))
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::Bool(_) => "bool",
+            Self::Builtin(_) => "builtin",
+            Self::Int(_) => "int",
+            Self::String(_) => "string",
+            Self::Table(_) => "table",
+            Self::Float(_) => "float",
+            Self::Nil => "nil",
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Self::String(Rc::new(value))
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Self::String(Rc::new(value.to_string()))
+    }
+}
+
+impl From<Table> for Value {
+    fn from(value: Table) -> Self {
+        Self::Table(value)
+    }
+}
+
+fn is_ident(s: &str) -> bool {
+    let mut chars = s.chars();
+    let starts_ok = chars
+        .next()
+        .map(|c| c.is_ascii_alphabetic() || c == '_')
+        .unwrap_or(false);
+    let reserved = matches!(
+        s,
+        "nil" | "true" | "false" | "not" | "and" | "or" | "local" | "function" | "module"
+    );
+    starts_ok && !reserved && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn fmt_string(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '\\' => write!(f, "\\\\")?,
+            '"' => write!(f, "\\\"")?,
+            '\t' => write!(f, "\\t")?,
+            '\r' => write!(f, "\\r")?,
+            '\n' => write!(f, "\\n")?,
+            c if c.is_control() => write!(f, "\\u{{{:x}}}", c as u32)?,
+            c => write!(f, "{c}")?,
+        }
+    }
+    write!(f, "\"")
+}
+
+fn fmt_value(f: &mut fmt::Formatter<'_>, value: &Value, path: &mut HashSet<Table>) -> fmt::Result {
+    match value {
+        Value::Bool(b) => write!(f, "{b}"),
+        Value::Builtin(b) => write!(f, "{b:?}"),
+        Value::Int(i) => write!(f, "{i}"),
+        Value::String(s) => fmt_string(f, s),
+        // Debug formatting always includes a decimal point or exponent, so the
+        // result parses as a float literal again (except for inf and NaN).
+        Value::Float(n) => write!(f, "{n:?}"),
+        Value::Nil => write!(f, "nil"),
+        Value::Table(t) => fmt_table(f, t, path),
+    }
+}
+
+fn fmt_table(f: &mut fmt::Formatter<'_>, table: &Table, path: &mut HashSet<Table>) -> fmt::Result {
+    if path.contains(table) {
+        return write!(f, "{{..}}");
+    }
+    path.insert(table.clone());
+
+    if table.is_empty() {
+        write!(f, "{{}}")?;
+    } else {
+        write!(f, "{{ ")?;
+        let len = table.len();
+        for (i, (key, value)) in table.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            match key.value() {
+                // The first entries are the positional elements
+                _ if i < len => {}
+                Value::String(s) if is_ident(s) => write!(f, "{s}: ")?,
+                key => {
+                    write!(f, "[")?;
+                    fmt_value(f, key, path)?;
+                    write!(f, "]: ")?;
+                }
+            }
+            fmt_value(f, &value, path)?;
+        }
+        write!(f, " }}")?;
+    }
+
+    path.remove(table);
+    Ok(())
+}
+
+/// Values are displayed as the expression that would construct them.
+///
+/// Tables are displayed using table constructor syntax, not table literal
+/// syntax, since a table literal would be evaluated as code. Tables that
+/// contain themselves are abbreviated as `{..}` where they recur.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_value(f, self, &mut HashSet::new())
+    }
+}
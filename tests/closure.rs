@@ -0,0 +1,120 @@
+mod common;
+
+#[test]
+fn arg_is_bound_to_the_argument() {
+    let source = "'{
+        local function id(x) x,
+        local function raw() 'arg(),
+        { id(1), raw(2), id() },
+    }";
+    assert_eq!(common::run_ok(source), "{ 1, 2 }");
+}
+
+#[test]
+fn functions_can_recurse() {
+    // There are no conditionals yet, so the branch is picked from a table.
+    let source = "'{
+        local function fact(n) '{
+            local branches = {
+                [true]: function() n * fact(n - 1),
+                [false]: function() 1,
+            },
+            branches[n > 0](),
+        },
+        fact(10),
+    }";
+    assert_eq!(common::run_ok(source), "3628800");
+}
+
+#[test]
+fn nested_closures_capture_their_scope() {
+    let source = "'{
+        local function adder(a) function(b) function(c) a + b + c,
+        local add1 = adder(1),
+        { add1(2)(3), add1(20)(300), adder(4)(5)(6) },
+    }";
+    assert_eq!(common::run_ok(source), "{ 6, 321, 15 }");
+}
+
+#[test]
+fn closures_share_captured_variables() {
+    let source = "'{
+        local function counter() '{
+            local count = 0,
+            {
+                inc: function() count = count + 1,
+                get: function() count,
+            },
+        },
+        local c = counter(),
+        c.inc(),
+        c.inc(),
+        { c.get(), counter().get() },
+    }";
+    assert_eq!(common::run_ok(source), "{ 2, 0 }");
+}
+
+#[test]
+fn loop_variables_are_captured_per_iteration() {
+    // A loop from 0 to 3, written as recursion
+    let source = "'{
+        local fs = {},
+        local function loop(i) '{
+            fs[i] = function() i * 10,
+            local branches = {
+                [true]: function() loop(i + 1),
+                [false]: function() nil,
+            },
+            branches[i < 2](),
+        },
+        loop(0),
+        { fs[0](), fs[1](), fs[2]() },
+    }";
+    assert_eq!(common::run_ok(source), "{ 0, 10, 20 }");
+}
+
+#[test]
+fn only_exact_closures_are_called_as_closures() {
+    let source = "'{
+        local s = {},
+        local hook = function(x) x[1],
+        local called = 'setmeta{ { scope: s }, { __call: hook } },
+        local extra = 'setmeta{ { 1, scope: s, x: 2 }, { __call: hook } },
+        { called(3), extra(4) },
+    }";
+    assert_eq!(common::run_ok(source), "{ 3, 4 }");
+    let stderr = common::run_err("{ 1, scope: {}, x: 2 }(3)");
+    assert!(stderr.contains("cannot call table"), "{stderr}");
+}
+
+#[test]
+fn calling_other_tables_is_an_error() {
+    let stderr = common::run_err("{ 1, 2 }(3)");
+    assert!(stderr.contains("cannot call table"), "{stderr}");
+}
+
+#[test]
+fn functions_can_recurse_deeply() {
+    let source = "'{
+        local function count(n) '{
+            local branches = {
+                [true]: function() count(n - 1) + 1,
+                [false]: function() 0,
+            },
+            branches[n > 0](),
+        },
+        count(5000),
+    }";
+    assert_eq!(common::run_ok(source), "5000");
+}
+
+#[test]
+fn deep_recursion_is_a_stack_overflow() {
+    let source = "'{
+        local function forever(n) forever(n + 1),
+        forever(0),
+    }";
+    let stderr = common::run_err(source);
+    assert!(stderr.contains("stack overflow"), "{stderr}");
+    assert!(!stderr.contains("fatal runtime error"), "{stderr}");
+}
@@ -0,0 +1,70 @@
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Output};
+
+/// Run tada with the given arguments in a directory.
+#[allow(dead_code)] // Not every test uses every helper
+pub fn run_in(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_tada"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("failed to run tada")
+}
+
+/// Run a tada subcommand on a file with the given contents.
+#[allow(dead_code)] // Not every test uses every helper
+pub fn command(subcommand: &str, source: &str, args: &[&str]) -> Output {
+    let mut file = tempfile::Builder::new()
+        .suffix(".tada")
+        .tempfile()
+        .expect("failed to create temporary file");
+    file.write_all(source.as_bytes())
+        .expect("failed to write temporary file");
+
+    Command::new(env!("CARGO_BIN_EXE_tada"))
+        .arg(subcommand)
+        .args(args)
+        .arg(file.path())
+        .output()
+        .expect("failed to run tada")
+}
+
+/// Run a tada program with `tada run` and the given extra arguments.
+#[allow(dead_code)] // Not every test uses every helper
+pub fn run(source: &str, args: &[&str]) -> Output {
+    command("run", source, args)
+}
+
+/// Run a tada program and return what it printed to stdout.
+///
+/// Panics if the program fails.
+#[allow(dead_code)] // Not every test uses every helper
+pub fn run_ok(source: &str) -> String {
+    let output = run(source, &[]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "program failed: {stderr}");
+    String::from_utf8(output.stdout)
+        .expect("non-utf8 output")
+        .trim_end()
+        .to_string()
+}
+
+/// Run a tada subcommand that is expected to fail and return what it printed
+/// to stderr.
+#[allow(dead_code)] // Not every test uses every helper
+pub fn stderr_of(subcommand: &str, source: &str) -> String {
+    let output = command(subcommand, source, &[]);
+    assert!(
+        !output.status.success(),
+        "{subcommand} unexpectedly succeeded"
+    );
+    String::from_utf8(output.stderr).expect("non-utf8 output")
+}
+
+/// Run a tada program that is expected to fail and return what it printed to
+/// stderr.
+#[allow(dead_code)] // Not every test uses every helper
+pub fn run_err(source: &str) -> String {
+    stderr_of("run", source)
+}
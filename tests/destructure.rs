@@ -0,0 +1,139 @@
+mod common;
+
+#[test]
+fn positional_and_named_elements() {
+    let source = "'{
+        local { a, b, foo: c } = { 1, 2, foo: 3, bar: 4 },
+        { a, b, c },
+    }";
+    assert_eq!(common::run_ok(source), "{ 1, 2, 3 }");
+}
+
+#[test]
+fn local_defines_variables_in_the_current_scope() {
+    let source = "'{
+        local a = 0,
+        '{ local { a } = { 1 } },
+        a,
+    }";
+    assert_eq!(common::run_ok(source), "0");
+}
+
+#[test]
+fn non_local_assigns_to_the_defining_scope() {
+    let source = "'{
+        local a = 0,
+        '{ { a } = { 1 } },
+        a,
+    }";
+    assert_eq!(common::run_ok(source), "1");
+}
+
+#[test]
+fn function_parameters() {
+    let source = "'{
+        local function f{ x, y: z } x - z,
+        f{ 5, y: 3 },
+    }";
+    assert_eq!(common::run_ok(source), "2");
+}
+
+#[test]
+fn missing_keys_are_an_error_at_the_pattern() {
+    let stderr = common::run_err("local { a, b: c } = { 1 }");
+    assert!(stderr.contains("no key \"b\""), "{stderr}");
+    assert!(stderr.contains(":1:7\n"), "{stderr}");
+    assert!(stderr.contains("|       ^^^^^^^^^^^\n"), "{stderr}");
+}
+
+#[test]
+fn only_tables_can_be_destructured() {
+    let stderr = common::run_err("local { a } = 1");
+    assert!(stderr.contains("can't destructure int"), "{stderr}");
+    assert!(stderr.contains(":1:7\n"), "{stderr}");
+    assert!(stderr.contains("|       ^^^^^\n"), "{stderr}");
+}
+
+#[test]
+fn nested_patterns() {
+    let source = "'{
+        local entity = { \"ghost\", pos: { 3, 4 } },
+        local { name, pos: { x, y } } = entity,
+        { name, x, y },
+    }";
+    assert_eq!(common::run_ok(source), "{ \"ghost\", 3, 4 }");
+}
+
+#[test]
+fn nested_patterns_in_function_parameters() {
+    let source = "'{
+        local function dist{ { x1, y1 }, { x2, y2 } } x2 - x1 + y2 - y1,
+        dist{ { 1, 2 }, { 4, 6 } },
+    }";
+    assert_eq!(common::run_ok(source), "7");
+}
+
+#[test]
+fn errors_point_at_the_nested_pattern() {
+    let stderr = common::run_err("local { pos: { x, y } } = { pos: { 1 } }");
+    assert!(stderr.contains("no key 1"), "{stderr}");
+    assert!(stderr.contains(":1:14\n"), "{stderr}");
+    assert!(stderr.contains("|              ^^^^^^^^\n"), "{stderr}");
+}
+
+#[test]
+fn defaults_are_used_for_missing_keys() {
+    let source = "'{
+        local function f{ a, b = 2, c: d = a * 10 } { a, b, d },
+        { f{ 1 }, f{ 1, 3, c: 4 } },
+    }";
+    assert_eq!(common::run_ok(source), "{ { 1, 2, 10 }, { 1, 3, 4 } }");
+}
+
+#[test]
+fn defaults_are_only_evaluated_if_needed() {
+    let source = "'{
+        local calls = 0,
+        local function count() '{ calls = calls + 1, calls },
+        local { a = count() } = { 1 },
+        local { b = count() } = {},
+        { a, b, calls },
+    }";
+    assert_eq!(common::run_ok(source), "{ 1, 1, 1 }");
+}
+
+#[test]
+fn nested_patterns_can_have_defaults() {
+    let source = "'{
+        local { pos: { x, y } = { 0, 0 } } = {},
+        { x, y },
+    }";
+    assert_eq!(common::run_ok(source), "{ 0, 0 }");
+}
+
+#[test]
+fn rest_collects_remaining_entries() {
+    let source = "'{
+        local function f{ a, b = 2, ..opts } { a, b, opts },
+        { f{ 1 }, f{ 1, 3, x: 4, verbose: true } },
+    }";
+    assert_eq!(
+        common::run_ok(source),
+        "{ { 1, 2, {} }, { 1, 3, { x: 4, verbose: true } } }"
+    );
+}
+
+#[test]
+fn nested_patterns_can_have_rest() {
+    let source = "'{
+        local { pos: { x, ..more }, ..other } = { pos: { 1, z: 2 }, q: 3 },
+        { x, more, other },
+    }";
+    assert_eq!(common::run_ok(source), "{ 1, { z: 2 }, { q: 3 } }");
+}
+
+#[test]
+fn patterns_have_at_most_one_rest() {
+    let stderr = common::run_err("local { ..a, ..b } = {}");
+    assert!(stderr.contains("at most one rest"), "{stderr}");
+}
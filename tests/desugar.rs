@@ -0,0 +1,157 @@
+mod common;
+
+use std::time::{Duration, Instant};
+
+fn desugar(source: &str, args: &[&str]) -> String {
+    let output = common::command("desugar", source, args);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "desugar failed: {stderr}");
+    String::from_utf8(output.stdout).expect("non-utf8 output")
+}
+
+#[test]
+fn steps() {
+    assert_eq!(
+        desugar("-a", &["--steps"]),
+        "\
+# step 0
+-a
+# step 1: operator -a at 1:1-1:3
+'neg(a)
+# step 2: call a(b) at 1:1-1:3
+'{ call: 'neg, arg: a }
+# step 3: var foo at 1:2-1:3
+'{ call: 'neg, arg: [\"a\"] }
+# step 4: var [a] at 1:2-1:3
+'{ call: 'neg, arg: 'scope()[\"a\"] }
+# step 5: field a[b] at 1:2-1:3
+'{ call: 'neg, arg: 'get{ 'scope(), \"a\" } }
+# step 6: call a{..} at 1:2-1:3
+'{ call: 'neg, arg: 'get({ 'scope(), \"a\" }) }
+# step 7: call a(b) at 1:2-1:3
+'{ call: 'neg, arg: '{ call: 'get, arg: { 'scope(), \"a\" } } }
+# step 8: constructor { a, b, foo: c } at 1:2-1:3
+'{ call: 'neg, arg: '{ call: 'get, arg: '{ raw: '{ 'scope(), \"a\" } } } }
+# step 9: call a() at 1:2-1:3
+'{ call: 'neg, arg: '{ call: 'get, arg: '{ raw: '{ 'scope(nil), \"a\" } } } }
+# step 10: call a(b) at 1:2-1:3
+'{ call: 'neg, arg: '{ call: 'get, arg: '{ raw: '{ '{ call: 'scope, arg: nil }, \"a\" } } } }
+# step 11: literal '{ .., foo: nil } at 1:2-1:3
+'{ call: 'neg, arg: '{ call: 'get, arg: '{ raw: '{ '{ call: 'scope }, \"a\" } } } }
+"
+    );
+}
+
+#[test]
+fn final_program() {
+    assert_eq!(
+        desugar("-a", &["--final"]),
+        "'{ call: 'neg, arg: '{ call: 'get, arg: '{ raw: '{ '{ call: 'scope }, \"a\" } } } }\n"
+    );
+
+    // Literals are already fully desugared
+    assert_eq!(desugar("'{ 1, a: 2 }", &["--final"]), "'{ 1, a: 2 }\n");
+}
+
+#[test]
+fn max_steps() {
+    assert_eq!(desugar("-a", &["--final", "--max-steps", "0"]), "-a\n");
+    assert_eq!(
+        desugar("-a", &["--final", "--max-steps", "2"]),
+        "'{ call: 'neg, arg: a }\n"
+    );
+    assert_eq!(
+        desugar("-a", &["--steps", "--max-steps", "1"]),
+        "# step 0\n-a\n# step 1: operator -a at 1:1-1:3\n'neg(a)\n"
+    );
+}
+
+#[test]
+fn rules_and_spans() {
+    let output = desugar("local function f{a} a.x = {1, [b]: a + 1}\n", &["--steps"]);
+    let headers = output
+        .lines()
+        .filter(|l| l.starts_with("# step"))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        headers[..3],
+        [
+            "# step 0",
+            "# step 1: function local function foo{..} a at 1:1-1:42",
+            "# step 2: var local foo = a at 1:1-1:42",
+        ]
+    );
+    assert!(headers.contains(&"# step 24: field a.foo = b at 1:21-1:42"));
+    assert!(headers.contains(&"# step 38: constructor { .., [a]: b } at 1:27-1:42"));
+    assert!(headers.contains(&"# step 52: operator a + b at 1:36-1:41"));
+}
+
+#[test]
+fn diffs_have_no_headers_by_default() {
+    // `echo` shows which files would be diffed, one line per step
+    let output = desugar("-a", &["--difftool", "echo"]);
+    let steps = desugar("-a", &["--steps"]).matches("# step").count() - 1;
+    let lines = output.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), steps, "{output}");
+    assert!(lines.iter().all(|l| l.ends_with(".tada")), "{output}");
+}
+
+#[test]
+fn long_programs_are_desugared_in_linear_time() {
+    // Desugaring one step at a time walks the whole program for every step
+    let mut source = String::from("'{\n    local x = 1,\n");
+    for i in 0..1000 {
+        source += &format!("    x = (x * 3 + {i}) % 1000 - x / 7 + -{i} * 2,\n");
+    }
+    source += "    x,\n}\n";
+
+    let start = Instant::now();
+    assert_eq!(common::run_ok(&source), "-1370");
+    assert!(start.elapsed() < Duration::from_secs(20));
+}
+
+#[test]
+fn only_some_kinds() {
+    assert_eq!(
+        desugar("f{a.b, function(x) -x}\n", &["--final", "--only", "field"]),
+        "f{ 'get{ a, \"b\" }, function(x) -x }\n"
+    );
+    assert_eq!(
+        desugar(
+            "f{a.b, function(x) -x}\n",
+            &["--final", "--only", "neg,var"]
+        ),
+        "'scope()[\"f\"]{ 'scope()[\"a\"].b, function(x) 'neg('scope()[\"x\"]) }\n"
+    );
+
+    // Defaults in patterns are desugared even if the pattern is kept
+    assert_eq!(
+        desugar("{a = -1} = t\n", &["--final", "--only", "neg"]),
+        "{ a = 'neg(1) } = t\n"
+    );
+}
+
+#[test]
+fn only_rejects_unknown_kinds() {
+    let output = common::command("desugar", "a", &["--only", "field,frobnicate"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn steps_and_final_conflict() {
+    let output = common::command("desugar", "a", &["--steps", "--final"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn final_program_is_the_last_step() {
+    // `--final` desugars in one go, `--steps` one rule at a time
+    let source = "module\n  f: function{a, b = -1} a.x + b,\n  g: function(x) 'arg(),\n";
+    for only in [vec![], vec!["--only", "var,call"], vec!["--only", "module"]] {
+        let steps = desugar(source, &[&only[..], &["--steps"]].concat());
+        let (_, last) = steps.rsplit_once("# step ").unwrap();
+        let (_, last) = last.split_once('\n').unwrap();
+        let final_only = desugar(source, &[&only[..], &["--final"]].concat());
+        assert_eq!(final_only, last);
+    }
+}
@@ -0,0 +1,130 @@
+mod common;
+
+const BROKEN: &str = "'{\n  a = 1,\n  b = }\n";
+
+#[test]
+fn parse_errors_point_at_the_source() {
+    let stderr = common::run_err(BROKEN);
+    assert!(stderr.starts_with("error: unexpected '}'\n"), "{stderr}");
+    assert!(stderr.contains(".tada:3:7\n"), "{stderr}");
+    assert!(stderr.contains("3 |   b = }\n"), "{stderr}");
+    assert!(stderr.contains("  |       ^\n"), "{stderr}");
+    assert!(stderr.contains("= expected one of "), "{stderr}");
+}
+
+#[test]
+fn every_subcommand_reports_parse_errors() {
+    for subcommand in ["parse", "pretty", "desugar", "run"] {
+        let stderr = common::stderr_of(subcommand, BROKEN);
+        assert!(stderr.contains(".tada:3:7\n"), "{subcommand}: {stderr}");
+    }
+}
+
+#[test]
+fn unclosed_delimiters_point_at_the_opening_one() {
+    let stderr = common::stderr_of("parse", "{ a: 1");
+    assert!(
+        stderr.starts_with("error: unclosed delimiter '{'\n"),
+        "{stderr}"
+    );
+    assert!(stderr.contains(".tada:1:7\n"), "{stderr}");
+    assert!(stderr.contains("  |       ^\n"), "{stderr}");
+    assert!(stderr.contains("= '{' was opened here ("), "{stderr}");
+    assert!(stderr.contains(".tada:1:1)\n"), "{stderr}");
+    assert_eq!(stderr.matches("error:").count(), 1, "{stderr}");
+}
+
+#[test]
+fn columns_count_characters() {
+    let stderr = common::run_err("\"äöü\" + }");
+    assert!(stderr.contains(".tada:1:9\n"), "{stderr}");
+}
+
+#[test]
+fn runtime_errors_point_at_the_source() {
+    let stderr = common::run_err("1 + \"a\"");
+    assert!(
+        stderr.starts_with("error: 'add expected a number"),
+        "{stderr}"
+    );
+    assert!(stderr.contains(".tada:1:1\n"), "{stderr}");
+    assert!(stderr.contains("  | ^^^^^^^\n"), "{stderr}");
+}
+
+#[test]
+fn runtime_errors_name_the_sugar() {
+    let stderr = common::run_err("'{\n  a = 1,\n  a.foo,\n}\n");
+    assert!(stderr.contains(".tada:3:3\n"), "{stderr}");
+    assert!(stderr.contains("= in field access `a.foo` ("), "{stderr}");
+
+    let stderr = common::run_err("'{\n  a = 1,\n  a.b = {\n    2\n  },\n}\n");
+    assert!(
+        stderr.contains("= in field assignment `a.b = { ..` ("),
+        "{stderr}"
+    );
+
+    let stderr = common::run_err("t = { 1, [nil]: 2 }");
+    assert!(stderr.contains(".tada:1:10\n"), "{stderr}");
+    assert!(
+        stderr.contains("= in table constructor `{ 1, [nil]: 2 }` ("),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn destructuring_errors_name_the_sugar() {
+    // The error points at the nested pattern, the note at the whole sugar
+    let stderr = common::run_err("local { pos: { x, y } } = { pos: { 1 } }");
+    assert!(stderr.contains(".tada:1:14\n"), "{stderr}");
+    assert!(
+        stderr.contains("= in destructuring `local { pos: { x, y } } = { pos: { 1 } }` ("),
+        "{stderr}"
+    );
+    assert!(stderr.contains(".tada:1:1)\n"), "{stderr}");
+
+    let stderr = common::run_err("'{ local function f{ a, b } a, f{ 1 } }");
+    assert!(
+        stderr.contains("= in function definition `local function f{ a, b } a` ("),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn carriage_returns_are_not_part_of_the_line() {
+    let stderr = common::run_err("'{\r\n  a = 1,\r\n  b = }\r\n");
+    assert!(stderr.contains(".tada:3:7\n"), "{stderr}");
+    assert!(stderr.contains("3 |   b = }\n"), "{stderr}");
+}
+
+#[test]
+fn multi_line_spans_are_underlined_on_their_first_line() {
+    let stderr = common::run_err("1 +\n\"a\"");
+    assert!(stderr.contains("1 | 1 +\n"), "{stderr}");
+    assert!(stderr.contains("  | ^^^\n"), "{stderr}");
+}
+
+#[test]
+fn all_errors_are_reported_at_once() {
+    let source = "'{\n  a = 1 +,\n  b = (2 *),\n  c = f(1 +, 2),\n  d = 4,\n  { x y },\n}\n";
+    let stderr = common::run_err(source);
+    assert_eq!(stderr.matches("error:").count(), 4, "{stderr}");
+    for location in [":2:10\n", ":3:11\n", ":4:12\n", ":6:7\n"] {
+        assert!(stderr.contains(location), "{location}: {stderr}");
+    }
+}
+
+#[test]
+fn errors_in_modules_are_recovered_from() {
+    let source = "module\n  a: 1 +,\n  b: 2,\n  c: * 3\n";
+    let stderr = common::stderr_of("parse", source);
+    assert_eq!(stderr.matches("error:").count(), 2, "{stderr}");
+}
+
+#[test]
+fn partial_programs_contain_error_nodes() {
+    let output = common::command("parse", "{ 1, 2 +, 3 }", &[]);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("Partial parse: "), "{stdout}");
+    assert!(stdout.contains("Expr::Error("), "{stdout}");
+}
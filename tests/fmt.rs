@@ -0,0 +1,140 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+const UNFORMATTED: &str = "'{a=1,b=2}\n";
+const FORMATTED: &str = "'{ a = 1, b = 2 }\n";
+
+fn read(path: impl AsRef<Path>) -> String {
+    fs::read_to_string(path).expect("failed to read file")
+}
+
+#[test]
+fn files_are_rewritten_in_place() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("a.tada"), UNFORMATTED).unwrap();
+    fs::write(dir.path().join("b.tada"), UNFORMATTED).unwrap();
+
+    let output = common::run_in(dir.path(), &["fmt", "a.tada", "b.tada"]);
+    assert!(output.status.success());
+    assert_eq!(read(dir.path().join("a.tada")), FORMATTED);
+    assert_eq!(read(dir.path().join("b.tada")), FORMATTED);
+}
+
+#[test]
+fn directories_are_searched_for_tada_files() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("sub")).unwrap();
+    fs::write(dir.path().join("sub/a.tada"), UNFORMATTED).unwrap();
+    fs::write(dir.path().join("sub/notes.txt"), UNFORMATTED).unwrap();
+
+    let output = common::run_in(dir.path(), &["fmt", "."]);
+    assert!(output.status.success());
+    assert_eq!(read(dir.path().join("sub/a.tada")), FORMATTED);
+    assert_eq!(read(dir.path().join("sub/notes.txt")), UNFORMATTED);
+}
+
+#[test]
+fn check_shows_diff_without_writing() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("a.tada"), UNFORMATTED).unwrap();
+
+    let output = common::run_in(dir.path(), &["fmt", "--check", "a.tada"]);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("-'{a=1,b=2}\n"), "{stdout}");
+    assert!(stdout.contains("+'{ a = 1, b = 2 }\n"), "{stdout}");
+    assert_eq!(read(dir.path().join("a.tada")), UNFORMATTED);
+}
+
+#[test]
+fn check_accepts_formatted_files() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("a.tada"), FORMATTED).unwrap();
+
+    let output = common::run_in(dir.path(), &["fmt", "--check", "a.tada"]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn files_with_syntax_errors_are_left_alone() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("a.tada"), "'{a=}\n").unwrap();
+    fs::write(dir.path().join("b.tada"), UNFORMATTED).unwrap();
+
+    let output = common::run_in(dir.path(), &["fmt", "a.tada", "b.tada"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("error"));
+    assert_eq!(read(dir.path().join("a.tada")), "'{a=}\n");
+    assert_eq!(read(dir.path().join("b.tada")), FORMATTED);
+}
+
+#[test]
+fn width_from_flag() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("a.tada"), UNFORMATTED).unwrap();
+
+    let output = common::run_in(dir.path(), &["fmt", "--width", "10", "a.tada"]);
+    assert!(output.status.success());
+    assert_eq!(
+        read(dir.path().join("a.tada")),
+        "'{\n    a = 1,\n    b = 2\n}\n"
+    );
+}
+
+#[test]
+fn width_from_config_file() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("tadafmt.toml"), "# Narrow\nwidth = 10\n").unwrap();
+    fs::create_dir(dir.path().join("sub")).unwrap();
+    fs::write(dir.path().join("sub/a.tada"), UNFORMATTED).unwrap();
+
+    // The config file is found in a parent directory
+    let output = common::run_in(&dir.path().join("sub"), &["fmt", "a.tada"]);
+    assert!(output.status.success());
+    assert_eq!(
+        read(dir.path().join("sub/a.tada")),
+        "'{\n    a = 1,\n    b = 2\n}\n"
+    );
+
+    // The flag takes precedence
+    fs::write(dir.path().join("sub/a.tada"), UNFORMATTED).unwrap();
+    let output = common::run_in(
+        &dir.path().join("sub"),
+        &["fmt", "--width", "100", "a.tada"],
+    );
+    assert!(output.status.success());
+    assert_eq!(read(dir.path().join("sub/a.tada")), FORMATTED);
+}
+
+#[test]
+fn invalid_config_file() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("tadafmt.toml"), "indent = 2\n").unwrap();
+    fs::write(dir.path().join("a.tada"), UNFORMATTED).unwrap();
+
+    let output = common::run_in(dir.path(), &["fmt", "a.tada"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("unknown key \"indent\""), "{stderr}");
+    assert_eq!(read(dir.path().join("a.tada")), UNFORMATTED);
+}
+
+#[test]
+fn config_file_is_found_from_the_formatted_file() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("sub")).unwrap();
+    fs::write(dir.path().join("sub/tadafmt.toml"), "width = 10\n").unwrap();
+    fs::write(dir.path().join("sub/a.tada"), UNFORMATTED).unwrap();
+    fs::write(dir.path().join("b.tada"), UNFORMATTED).unwrap();
+
+    let output = common::run_in(dir.path(), &["fmt", "sub/a.tada", "b.tada"]);
+    assert!(output.status.success());
+    assert_eq!(
+        read(dir.path().join("sub/a.tada")),
+        "'{\n    a = 1,\n    b = 2\n}\n"
+    );
+    assert_eq!(read(dir.path().join("b.tada")), FORMATTED);
+}
@@ -0,0 +1,81 @@
+mod common;
+
+/// Parse the line printed by `--gc-stats` into `(collected, live)`.
+fn gc_stats(source: &str) -> (usize, usize) {
+    let output = common::run(source, &["--gc-stats"]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let line = stderr
+        .lines()
+        .find(|l| l.starts_with("gc: "))
+        .expect("no gc stats printed");
+
+    let words = line.split_whitespace().collect::<Vec<_>>();
+    let number_before = |word: &str| {
+        let i = words
+            .iter()
+            .position(|w| w.trim_end_matches(',') == word)
+            .unwrap();
+        words[i - 1].parse::<usize>().unwrap()
+    };
+    (number_before("collected"), number_before("live"))
+}
+
+#[test]
+fn unreachable_cycles_are_collected() {
+    let source = "'{
+        a = {},
+        a.self = a,
+        b = { a, c: {} },
+        b.c.parent = b,
+        a = nil,
+        b = nil,
+        nil,
+    }";
+    // Only the global scope survives. a, b and b.c need to be collected, the
+    // block scope and its metatable are freed when the block ends.
+    assert_eq!(gc_stats(source), (3, 1));
+}
+
+#[test]
+fn reachable_cycles_are_kept() {
+    let source = "'{
+        a = {},
+        a.self = a,
+        b = { a },
+    }";
+    // The global scope, a, b and the program's block scope with its metatable
+    assert_eq!(gc_stats(source), (0, 5));
+}
+
+#[test]
+fn cycles_through_the_scope_are_kept() {
+    let source = "'{
+        a = { scope: 'scope() },
+        a.scope.a.scope.b = 1,
+        b,
+    }";
+    assert_eq!(common::run_ok(source), "1");
+    // The global scope, a and the program's block scope with its metatable
+    assert_eq!(gc_stats(source), (0, 4));
+}
+
+#[test]
+fn scopes_of_finished_calls_are_collected() {
+    let source = |calls: usize| {
+        format!(
+            "'{{
+                local function make() '{{ local function inner() 1, 0 }},
+                {}
+                nil,
+            }}",
+            "make(), ".repeat(calls)
+        )
+    };
+    // Every call's scope and the closure of inner reference each other, so
+    // each finished call leaves the same cyclic garbage behind. Only the global
+    // scope survives.
+    let (once, live) = gc_stats(&source(1));
+    assert_eq!(live, 1);
+    assert_eq!(gc_stats(&source(3)), (once + 10, 1));
+}
@@ -0,0 +1,80 @@
+mod common;
+
+#[test]
+fn getmeta_returns_what_setmeta_set() {
+    let source = "'{
+        m = {},
+        t = 'setmeta{ {}, m },
+        { 'getmeta(t) == m, 'getmeta({}), 'getmeta(1) },
+    }";
+    assert_eq!(common::run_ok(source), "{ true }");
+}
+
+#[test]
+fn index_falls_back_to_other_tables() {
+    let source = "'{
+        base = { a: 1, b: 1 },
+        middle = 'setmeta{ { b: 2 }, { __index: base } },
+        t = 'setmeta{ { c: 3 }, { __index: middle } },
+        { t.a, t.b, t.c, 'getraw{ t, \"a\" } },
+    }";
+    assert_eq!(common::run_ok(source), "{ 1, 2, 3 }");
+}
+
+#[test]
+fn newindex_redirects_new_keys() {
+    let source = "'{
+        store = {},
+        t = 'setmeta{ { a: 1 }, { __newindex: store } },
+        t.a = 2,
+        t.b = 3,
+        'setraw{ t, \"c\", 4 },
+        { t, store },
+    }";
+    assert_eq!(common::run_ok(source), "{ { a: 2, c: 4 }, { b: 3 } }");
+}
+
+#[test]
+fn hooks_are_called() {
+    // `'getraw` receives `{t, x}` and returns `t[x]`.
+    let source = "'{
+        t = 'setmeta{ { 10, 20 }, { __call: 'getraw, __sub: 'getraw } },
+        { t(1), t - 0 },
+    }";
+    assert_eq!(common::run_ok(source), "{ 20, 10 }");
+}
+
+#[test]
+fn index_cycles_are_an_error() {
+    let source = "'{
+        t = {},
+        'setmeta{ t, { __index: t } },
+        t.missing,
+    }";
+    let stderr = common::run_err(source);
+    assert!(
+        stderr.contains("__index chain contains a cycle"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn cycles_after_the_start_of_a_chain_are_an_error() {
+    let setup = "
+        a = {}, b = {}, c = {}, d = {},
+        'setmeta{ a, { __index: b, __newindex: b } },
+        'setmeta{ b, { __index: c, __newindex: c } },
+        'setmeta{ c, { __index: d, __newindex: d } },
+        'setmeta{ d, { __index: b, __newindex: b } },
+    ";
+    let stderr = common::run_err(&format!("'{{ {setup} a.missing }}"));
+    assert!(
+        stderr.contains("__index chain contains a cycle"),
+        "{stderr}"
+    );
+    let stderr = common::run_err(&format!("'{{ {setup} a.missing = 1 }}"));
+    assert!(
+        stderr.contains("__newindex chain contains a cycle"),
+        "{stderr}"
+    );
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Parse and pretty print a program, failing if that takes longer than a few
+/// seconds.
+fn pretty_quickly(source: &str) {
+    let mut file = tempfile::Builder::new()
+        .suffix(".tada")
+        .tempfile()
+        .expect("failed to create temporary file");
+    file.write_all(source.as_bytes())
+        .expect("failed to write temporary file");
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_tada"))
+        .arg("pretty")
+        .arg(file.path())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("failed to run tada");
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        if let Some(status) = child.try_wait().expect("failed to wait for tada") {
+            assert!(status.success());
+            return;
+        }
+        if Instant::now() > deadline {
+            child.kill().expect("failed to kill tada");
+            panic!("parsing took too long");
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn nested_suffixes_are_parsed_in_linear_time() {
+    // Trying field and variable assignment before access used to parse every
+    // index twice, once per nesting level.
+    let depth = 30;
+    pretty_quickly(&format!("x{}{}", "[x".repeat(depth), "]".repeat(depth)));
+}
+
+#[test]
+fn chained_suffixes_are_parsed_in_linear_time() {
+    let depth = 20;
+    pretty_quickly(&format!(
+        "{}1{}.c = 3",
+        "x.a(2).b[".repeat(depth),
+        "].c".repeat(depth)
+    ));
+}
+
+#[test]
+fn nested_right_assoc_operators_are_parsed_in_linear_time() {
+    // The last operand of a right-associative chain used to be parsed twice
+    let depth = 30;
+    pretty_quickly(&format!("{}1{}", "x or (".repeat(depth), ")".repeat(depth)));
+}
+
+#[test]
+fn names_starting_with_underscores_are_not_numbers() {
+    for name in ["_1e5", "_1"] {
+        let output = common::command("parse", name, &[]);
+        assert!(output.status.success());
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(
+            stdout.contains(&format!("AccessIdent(\n        i#{name},")),
+            "{stdout}"
+        );
+    }
+    assert_eq!(common::run_ok("'{ local _1e5 = 3, _1e5 }"), "3");
+}
+
+#[test]
+fn number_literals_that_are_too_large_are_errors() {
+    let stderr = common::stderr_of("parse", "99999999999999999999999");
+    assert!(
+        stderr.starts_with("error: integer literal too large"),
+        "{stderr}"
+    );
+    let stderr = common::stderr_of("parse", "1e400");
+    assert!(
+        stderr.starts_with("error: float literal too large"),
+        "{stderr}"
+    );
+    assert_eq!(
+        common::run_ok("9_223_372_036_854_775_807"),
+        "9223372036854775807"
+    );
+    assert_eq!(common::run_ok("1.5e3"), "1500.0");
+}
+
+#[test]
+fn nested_table_constructors_are_parsed_in_linear_time() {
+    // Every table constructor used to be parsed as a pattern first, and its
+    // last element twice
+    let depth = 30;
+    pretty_quickly(&format!(
+        "{}1{}",
+        "{ a = ".repeat(depth),
+        " }".repeat(depth)
+    ));
+    let depth = 15;
+    pretty_quickly(&format!(
+        "{}1{}",
+        "{ a: { a = ".repeat(depth),
+        " } }".repeat(depth)
+    ));
+}
@@ -0,0 +1,136 @@
+mod common;
+
+use tada::pretty;
+
+fn pretty(source: &str) -> String {
+    pretty_width(source, 100)
+}
+
+fn pretty_width(source: &str, width: usize) -> String {
+    let output = common::command("pretty", source, &["--width", &width.to_string()]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "pretty failed: {stderr}");
+    String::from_utf8(output.stdout).expect("non-utf8 output")
+}
+
+#[test]
+fn comments_are_preserved() {
+    let source = "\
+# header
+'{
+    # about a
+    a = 1, # trailing
+    b = 2,
+    # dangling
+}
+# footer
+";
+    assert_eq!(pretty(source), source);
+}
+
+#[test]
+fn blank_lines_are_preserved_but_collapsed() {
+    let source = "\n\n'{\n    a = 1,\n\n\n\n    b = 2,\n\n}\n\n";
+    assert_eq!(pretty(source), "'{\n    a = 1,\n\n    b = 2,\n}\n");
+}
+
+#[test]
+fn comments_before_separators_move_after_them() {
+    let source = "{ a # about a\n, b }\n";
+    assert_eq!(pretty(source), "{\n    a, # about a\n    b\n}\n");
+}
+
+#[test]
+fn comments_in_delimiters_indent_their_contents() {
+    let source = "f( # about the arg\n3)\n";
+    assert_eq!(pretty(source), "f( # about the arg\n    3\n)\n");
+}
+
+#[test]
+fn comments_in_every_node_survive() {
+    let source = "\
+'{
+    local # 1
+    function # 2
+    f # 3
+    { # 4
+        a # 5
+        : # 6
+        b # 7
+        = # 8
+        1 # 9
+    } # 10
+    a.b # 11
+    . # 12
+    c # 13
+    = # 14
+    - # 15
+    not # 16
+    x # 17
+    + # 18
+    y[ # 19
+        z # 20
+    ] # 21
+    ( # 22
+    ),
+}
+";
+    let output = pretty(source);
+    for i in 1..=22 {
+        assert!(output.contains(&format!("# {i}\n")), "# {i}: {output}");
+    }
+    assert_eq!(pretty(&output), output);
+}
+
+#[test]
+fn comments_in_modules_are_stable() {
+    assert_eq!(pretty("module # m\n  a: 1\n"), "module # m\n\na: 1\n");
+    let sources = [
+        "module # m\n  a: 1\n",
+        "module\n  # m\n  a: 1\n",
+        "# top\nmodule\n\n  # m\n\n  a: 1, # a\n  b: 2 # b\n",
+        "module # m\n",
+    ];
+    for source in sources {
+        let output = pretty(source);
+        assert_eq!(pretty(&output), output, "{source:?}");
+    }
+}
+
+#[test]
+fn bin_op_chains_break_after_operators() {
+    let source = "aaaa + bbbb * cccc - dddd";
+    assert_eq!(pretty_width(source, 100), "aaaa + bbbb * cccc - dddd\n");
+    assert_eq!(
+        pretty_width(source, 20),
+        "aaaa +\n    bbbb * cccc -\n    dddd\n"
+    );
+}
+
+#[test]
+fn bin_op_chains_break_at_lowest_precedence_first() {
+    let source = "aaaa == bbbb and cccc or dddd + eeee";
+    assert_eq!(
+        pretty_width(source, 30),
+        "aaaa == bbbb and cccc or\n    dddd + eeee\n"
+    );
+    assert_eq!(
+        pretty_width(source, 20),
+        "aaaa == bbbb and\n    cccc or\n    dddd + eeee\n"
+    );
+}
+
+#[test]
+fn comments_before_operators_keep_the_chain_indented() {
+    assert_eq!(pretty("a # c\n+ 2\n"), "a # c\n    + 2\n");
+    assert_eq!(
+        pretty("'{ x = a + b # c\n+ 2 }\n"),
+        "'{\n    x = a +\n        b # c\n        + 2\n}\n"
+    );
+}
+
+#[test]
+fn comments_are_found_outside_of_strings() {
+    let source = "a # one \n\"# not \\\" # not\" # two\r\n#three";
+    assert_eq!(pretty::comments(source), [" one", " two", "three"]);
+}
@@ -0,0 +1,67 @@
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+/// Run the repl with the given input and return its output.
+fn repl(input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_tada"))
+        .arg("repl")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run tada");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .expect("failed to write input");
+    let output = child.wait_with_output().expect("failed to wait for tada");
+    assert!(output.status.success());
+    output
+}
+
+fn stdout(output: &Output) -> &str {
+    std::str::from_utf8(&output.stdout).expect("non-utf8 output")
+}
+
+fn stderr(output: &Output) -> &str {
+    std::str::from_utf8(&output.stderr).expect("non-utf8 output")
+}
+
+#[test]
+fn scope_persists_across_entries() {
+    let output = repl("a = 1\nlocal b = 2\nfunction f(x) x * 10\nf(a + b)\n");
+    assert_eq!(stdout(&output), "30\n");
+}
+
+#[test]
+fn incomplete_input_continues() {
+    let output = repl("'{\n  a: 1, # }\n  b: \"}\",\n}\n(1 +\n 2)\n");
+    assert_eq!(stdout(&output), "{ a: 1, b: \"}\" }\n3\n");
+    assert_eq!(stderr(&output), "");
+}
+
+#[test]
+fn errors_dont_end_session() {
+    let output = repl("1 +* 2\na = 1\nf = function() a.b\nf()\na\n");
+    assert_eq!(stdout(&output), "1\n");
+
+    // Errors point into the entry where the code was written
+    let stderr = stderr(&output);
+    assert!(stderr.contains("unexpected '*'"), "{stderr}");
+    assert!(stderr.contains("<repl>:1:4\n"), "{stderr}");
+    assert!(stderr.contains("<repl>:3:16\n"), "{stderr}");
+}
+
+#[test]
+fn meta_commands() {
+    let output = repl(":pretty {a=1,\n  b=2}\n:desugar -a\n:quit\n1\n");
+    assert_eq!(
+        stdout(&output),
+        "{ a = 1, b = 2 }\n'{ call: 'neg, arg: '{ call: 'get, arg: '{ raw: '{ '{ call: 'scope }, \"a\" } } } }\n"
+    );
+
+    let output = repl(":frobnicate\n");
+    assert!(stderr(&output).contains("unknown command :frobnicate"));
+}
@@ -0,0 +1,694 @@
+//! Check that parsing the pretty-printed version of a program gives back the
+//! same program, as compared by [`SpanlessEq`].
+//!
+//! Besides a few handwritten cases, this generates random programs from a
+//! fixed range of seeds. Run with `TADA_ROUNDTRIP_SEEDS=<n>` to try more seeds.
+
+use std::env;
+
+use chumsky::Parser;
+
+use tada::ast::{
+    BinOp, BoundedSeparated, Call, Expr, Field, FloatLit, FuncDef, Ident, Line, Lit, NumLit,
+    NumLitStr, Pattern, Program, Space, SpanlessEq, StringLit, StringLitElem, TableConstr,
+    TableConstrElem, TableDestr, TableLit, TableLitElem, TablePattern, TablePatternElem, Var,
+};
+use tada::builtin::Builtin;
+use tada::span::Span;
+use tada::{parser, pretty, span};
+
+const SEEDS: u64 = 100;
+const MAX_DEPTH: u32 = 5;
+
+const BIN_OPS: [BinOp; 13] = [
+    BinOp::Mul,
+    BinOp::Div,
+    BinOp::Mod,
+    BinOp::Add,
+    BinOp::Sub,
+    BinOp::Eq,
+    BinOp::Neq,
+    BinOp::Gt,
+    BinOp::Ge,
+    BinOp::Lt,
+    BinOp::Le,
+    BinOp::And,
+    BinOp::Or,
+];
+
+const BUILTINS: [Builtin; 24] = [
+    Builtin::Get,
+    Builtin::Set,
+    Builtin::GetRaw,
+    Builtin::SetRaw,
+    Builtin::GetMeta,
+    Builtin::SetMeta,
+    Builtin::Scope,
+    Builtin::Arg,
+    Builtin::Destructure,
+    Builtin::Neg,
+    Builtin::Not,
+    Builtin::Mul,
+    Builtin::Div,
+    Builtin::Mod,
+    Builtin::Add,
+    Builtin::Sub,
+    Builtin::Eq,
+    Builtin::Ne,
+    Builtin::Gt,
+    Builtin::Ge,
+    Builtin::Lt,
+    Builtin::Le,
+    Builtin::And,
+    Builtin::Or,
+];
+
+const IDENTS: [&str; 5] = ["a", "b", "foo", "bar_2", "e"];
+
+/// Parse a program, panicking on syntax errors.
+fn parse(source: &str) -> Program {
+    match parser::parser().parse(span::stream_from_str(source)) {
+        Ok(program) => program,
+        Err(errs) => panic!("failed to parse {source:?}: {errs:?}"),
+    }
+}
+
+/// Assert that a program survives a round trip through the pretty printer, at
+/// both a comfortable and a very small width.
+fn assert_roundtrip(program: &Program) {
+    for width in [100, 10] {
+        let printed = pretty::pretty_to_string(program.clone(), width);
+        let reparsed = parse(&printed);
+        assert!(
+            reparsed.spanless_eq(program),
+            "round trip at width {width} changed the program\n\
+             printed:\n{printed}\n\
+             original: {program:#?}\n\
+             reparsed: {reparsed:#?}"
+        );
+    }
+}
+
+/// A small xorshift generator, so the test needs no extra dependencies and
+/// every seed produces the same programs on every platform.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        // The state must never be zero.
+        Self(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A number in `0..n`.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    /// True with a probability of `1/n`.
+    fn one_in(&mut self, n: usize) -> bool {
+        self.below(n) == 0
+    }
+
+    fn choose<T: Clone>(&mut self, items: &[T]) -> T {
+        items[self.below(items.len())].clone()
+    }
+}
+
+/// Generates random, but syntactically valid ASTs.
+///
+/// Parentheses are only generated occasionally. It's up to the pretty printer
+/// to add them wherever precedence or the grammar require them. All spans are
+/// empty since they're ignored anyway.
+struct Gen {
+    rng: Rng,
+}
+
+impl Gen {
+    fn span(&self) -> Span {
+        Span::new(0, 0)
+    }
+
+    fn space(&mut self) -> Space {
+        let lines = match self.rng.below(8) {
+            0 => vec![Line::Comment(" comment".to_string())],
+            1 => vec![Line::Empty],
+            _ => vec![],
+        };
+        Space {
+            lines,
+            span: self.span(),
+        }
+    }
+
+    fn local(&mut self) -> Option<Space> {
+        self.rng.one_in(2).then(|| self.space())
+    }
+
+    fn ident(&mut self) -> Ident {
+        Ident::new(self.rng.choose(&IDENTS), self.span())
+    }
+
+    fn bounded_separated<E>(
+        &mut self,
+        max_elems: usize,
+        mut elem: impl FnMut(&mut Self) -> E,
+    ) -> BoundedSeparated<E> {
+        let elems = (0..self.rng.below(max_elems + 1))
+            .map(|_| (self.space(), elem(self), self.space()))
+            .collect();
+        let trailing = self.rng.one_in(2).then(|| self.space());
+        BoundedSeparated {
+            elems,
+            trailing,
+            span: self.span(),
+        }
+    }
+
+    fn num_lit(&mut self) -> NumLit {
+        let (value, str) = match self.rng.below(4) {
+            0 => (0, NumLitStr::Dec("0".to_string())),
+            1 => (12345, NumLitStr::Dec("12_345".to_string())),
+            2 => (2, NumLitStr::Bin("10".to_string())),
+            _ => (0xc0f3, NumLitStr::Hex("_c0_f3".to_string())),
+        };
+        NumLit {
+            value,
+            str,
+            span: self.span(),
+        }
+    }
+
+    fn float_lit(&mut self) -> FloatLit {
+        let str = self.rng.choose(&["1.5", "0.25e-3", "1e10", "1_0.0_1"]);
+        FloatLit {
+            value: str.replace('_', "").parse().unwrap(),
+            str: str.to_string(),
+            span: self.span(),
+        }
+    }
+
+    fn string_lit(&mut self) -> StringLit {
+        let mut elems = vec![];
+        for _ in 0..self.rng.below(4) {
+            let elem = match self.rng.below(8) {
+                0 => StringLitElem::Unicode('ä'),
+                1 => StringLitElem::Backslash,
+                2 => StringLitElem::DoubleQuote,
+                3 => StringLitElem::Tab,
+                4 => StringLitElem::CarriageReturn,
+                5 => StringLitElem::Newline,
+                // The parser merges consecutive plain text into one element.
+                _ if matches!(elems.last(), Some(StringLitElem::Plain(_))) => continue,
+                _ => StringLitElem::Plain("hello world".to_string()),
+            };
+            elems.push(elem);
+        }
+        StringLit {
+            elems,
+            span: self.span(),
+        }
+    }
+
+    fn table_lit_elem(&mut self, depth: u32) -> TableLitElem {
+        if self.rng.one_in(2) {
+            TableLitElem::Positional(self.expr(depth).boxed())
+        } else {
+            TableLitElem::Named {
+                name: self.ident(),
+                s0: self.space(),
+                s1: self.space(),
+                value: self.expr(depth).boxed(),
+                span: self.span(),
+            }
+        }
+    }
+
+    fn table_lit(&mut self, depth: u32) -> TableLit {
+        TableLit(self.bounded_separated(3, |gen| gen.table_lit_elem(depth)))
+    }
+
+    fn lit(&mut self, depth: u32) -> Lit {
+        let span = self.span();
+        match self.rng.below(if depth == 0 { 6 } else { 7 }) {
+            0 => Lit::Nil(span),
+            1 => Lit::Bool(self.rng.one_in(2), span),
+            2 => Lit::Builtin(self.rng.choose(&BUILTINS), span),
+            3 => Lit::Num(self.num_lit()),
+            4 => Lit::Float(self.float_lit()),
+            5 => Lit::String(self.string_lit()),
+            _ => Lit::Table(self.table_lit(depth - 1)),
+        }
+    }
+
+    fn table_constr(&mut self, depth: u32) -> TableConstr {
+        TableConstr(self.bounded_separated(3, |gen| {
+            if gen.rng.one_in(3) {
+                TableConstrElem::Indexed {
+                    s0: gen.space(),
+                    index: gen.expr(depth).boxed(),
+                    s1: gen.space(),
+                    s2: gen.space(),
+                    s3: gen.space(),
+                    value: gen.expr(depth).boxed(),
+                    span: gen.span(),
+                }
+            } else {
+                TableConstrElem::Lit(gen.table_lit_elem(depth))
+            }
+        }))
+    }
+
+    fn pattern(&mut self, depth: u32) -> Pattern {
+        if depth == 0 || self.rng.one_in(2) {
+            Pattern::Ident(self.ident())
+        } else {
+            Pattern::Table(self.table_pattern(depth - 1))
+        }
+    }
+
+    fn table_pattern(&mut self, depth: u32) -> TablePattern {
+        let mut rest = false;
+        TablePattern(self.bounded_separated(3, |gen| match gen.rng.below(5) {
+            0 => TablePatternElem::Positional(gen.pattern(depth)),
+            1 => TablePatternElem::PositionalDefault {
+                pattern: gen.pattern(depth),
+                s0: gen.space(),
+                s1: gen.space(),
+                default: gen.expr(depth).boxed(),
+                span: gen.span(),
+            },
+            2 => TablePatternElem::Named {
+                name: gen.ident(),
+                s0: gen.space(),
+                s1: gen.space(),
+                pattern: gen.pattern(depth),
+                span: gen.span(),
+            },
+            // A pattern can have at most one rest capture.
+            3 if !rest => {
+                rest = true;
+                TablePatternElem::Rest {
+                    s0: gen.space(),
+                    name: gen.ident(),
+                    span: gen.span(),
+                }
+            }
+            _ => TablePatternElem::NamedDefault {
+                name: gen.ident(),
+                s0: gen.space(),
+                s1: gen.space(),
+                pattern: gen.pattern(depth),
+                s2: gen.space(),
+                s3: gen.space(),
+                default: gen.expr(depth).boxed(),
+                span: gen.span(),
+            },
+        }))
+    }
+
+    fn call(&mut self, depth: u32) -> Call {
+        let expr = self.expr(depth).boxed();
+        match self.rng.below(3) {
+            0 => Call::Arg {
+                expr,
+                s0: self.space(),
+                s1: self.space(),
+                arg: self.expr(depth).boxed(),
+                s2: self.space(),
+                span: self.span(),
+            },
+            1 => Call::NoArg {
+                expr,
+                s0: self.space(),
+                s1: self.space(),
+                span: self.span(),
+            },
+            _ => Call::Constr {
+                expr,
+                s0: self.space(),
+                constr: self.table_constr(depth),
+                span: self.span(),
+            },
+        }
+    }
+
+    fn field(&mut self, depth: u32) -> Field {
+        let expr = self.expr(depth).boxed();
+        match self.rng.below(4) {
+            0 => Field::Access {
+                expr,
+                s0: self.space(),
+                s1: self.space(),
+                index: self.expr(depth).boxed(),
+                s2: self.space(),
+                span: self.span(),
+            },
+            1 => Field::Assign {
+                expr,
+                s0: self.space(),
+                s1: self.space(),
+                index: self.expr(depth).boxed(),
+                s2: self.space(),
+                s3: self.space(),
+                s4: self.space(),
+                value: self.expr(depth).boxed(),
+                span: self.span(),
+            },
+            2 => Field::AccessIdent {
+                expr,
+                s0: self.space(),
+                s1: self.space(),
+                ident: self.ident(),
+                span: self.span(),
+            },
+            _ => Field::AssignIdent {
+                expr,
+                s0: self.space(),
+                s1: self.space(),
+                ident: self.ident(),
+                s2: self.space(),
+                s3: self.space(),
+                value: self.expr(depth).boxed(),
+                span: self.span(),
+            },
+        }
+    }
+
+    fn var(&mut self, depth: u32) -> Var {
+        match self.rng.below(3) {
+            0 => Var::Access {
+                s0: self.space(),
+                index: self.expr(depth).boxed(),
+                s1: self.space(),
+                span: self.span(),
+            },
+            1 => Var::Assign {
+                local: self.local(),
+                s0: self.space(),
+                index: self.expr(depth).boxed(),
+                s1: self.space(),
+                s2: self.space(),
+                s3: self.space(),
+                value: self.expr(depth).boxed(),
+                span: self.span(),
+            },
+            _ => Var::AssignIdent {
+                local: self.local(),
+                name: self.ident(),
+                s0: self.space(),
+                s1: self.space(),
+                value: self.expr(depth).boxed(),
+                span: self.span(),
+            },
+        }
+    }
+
+    fn table_destr(&mut self, depth: u32) -> TableDestr {
+        TableDestr {
+            local: self.local(),
+            pattern: self.table_pattern(depth),
+            s0: self.space(),
+            s1: self.space(),
+            value: self.expr(depth).boxed(),
+            span: self.span(),
+        }
+    }
+
+    fn func_def(&mut self, depth: u32) -> FuncDef {
+        let body = self.expr(depth).boxed();
+        let span = self.span();
+        match self.rng.below(6) {
+            0 => FuncDef::AnonNoArg {
+                s0: self.space(),
+                s1: self.space(),
+                s2: self.space(),
+                body,
+                span,
+            },
+            1 => FuncDef::AnonArg {
+                s0: self.space(),
+                s1: self.space(),
+                arg: self.ident(),
+                s2: self.space(),
+                s3: self.space(),
+                body,
+                span,
+            },
+            2 => FuncDef::AnonDestr {
+                s0: self.space(),
+                pattern: self.table_pattern(depth),
+                s1: self.space(),
+                body,
+                span,
+            },
+            3 => FuncDef::NamedNoArg {
+                local: self.local(),
+                s0: self.space(),
+                name: self.ident(),
+                s1: self.space(),
+                s2: self.space(),
+                s3: self.space(),
+                body,
+                span,
+            },
+            4 => FuncDef::NamedArg {
+                local: self.local(),
+                s0: self.space(),
+                name: self.ident(),
+                s1: self.space(),
+                s2: self.space(),
+                arg: self.ident(),
+                s3: self.space(),
+                s4: self.space(),
+                body,
+                span,
+            },
+            _ => FuncDef::NamedDestr {
+                local: self.local(),
+                s0: self.space(),
+                name: self.ident(),
+                s1: self.space(),
+                pattern: self.table_pattern(depth),
+                s2: self.space(),
+                body,
+                span,
+            },
+        }
+    }
+
+    fn expr(&mut self, depth: u32) -> Expr {
+        if depth == 0 {
+            return match self.rng.below(2) {
+                0 => Expr::Lit(self.lit(0)),
+                _ => Expr::Var(Var::AccessIdent(self.ident())),
+            };
+        }
+
+        let depth = depth - 1;
+        let span = self.span();
+        // Operators are more likely so that long chains get generated.
+        match self.rng.below(14) {
+            0 => Expr::Lit(self.lit(depth + 1)),
+            1 => Expr::Call(self.call(depth)),
+            2 => Expr::Field(self.field(depth)),
+            3 => Expr::Var(self.var(depth)),
+            4 => Expr::TableConstr(self.table_constr(depth)),
+            5 => Expr::TableDestr(self.table_destr(depth)),
+            6 => Expr::FuncDef(self.func_def(depth)),
+            7 => Expr::Paren {
+                s0: self.space(),
+                inner: self.expr(depth).boxed(),
+                s1: self.space(),
+                span,
+            },
+            8 => Expr::Neg {
+                minus: span,
+                s0: self.space(),
+                expr: self.expr(depth).boxed(),
+                span,
+            },
+            9 => Expr::Not {
+                not: span,
+                s0: self.space(),
+                expr: self.expr(depth).boxed(),
+                span,
+            },
+            _ => Expr::BinOp {
+                left: self.expr(depth).boxed(),
+                s0: self.space(),
+                op: self.rng.choose(&BIN_OPS),
+                s1: self.space(),
+                right: self.expr(depth).boxed(),
+                span,
+            },
+        }
+    }
+
+    fn program(&mut self) -> Program {
+        if self.rng.one_in(4) {
+            Program::Module {
+                s0: self.space(),
+                elems: self.bounded_separated(3, |gen| gen.table_lit_elem(MAX_DEPTH)),
+                span: self.span(),
+            }
+        } else {
+            Program::Expr {
+                s0: self.space(),
+                expr: self.expr(MAX_DEPTH).boxed(),
+                s1: self.space(),
+                span: self.span(),
+            }
+        }
+    }
+}
+
+#[test]
+fn spanless_eq_ignores_layout() {
+    let a = parse("a   +(b*c) # comment\n");
+    let b = parse("(a + b * c)");
+    let c = parse("(a + b) * c");
+    assert!(a.spanless_eq(&b));
+    assert!(!a.spanless_eq(&c));
+}
+
+#[test]
+fn precedence() {
+    let sources = [
+        "a + b * c",
+        "(a + b) * c",
+        "a - (b - c)",
+        "(a - b) - c",
+        "a or b and c",
+        "(a or b) and c",
+        "a and (b and c)",
+        "(a and b) and c",
+        "a == (b == c)",
+        "-(a + b)",
+        "not (a or b)",
+        "(a + b)(c)",
+        "(a = b) + c",
+        "(function() a) + b",
+        "a + (b = c) + d",
+        "(-a.b = c) * d",
+    ];
+    for source in sources {
+        assert_roundtrip(&parse(source));
+    }
+}
+
+#[test]
+fn comments_indent_continuations() {
+    let cases = [
+        ("t.x # c\n.y", "t.x # c\n    .y\n"),
+        ("t. # c\ny", "t. # c\n    y\n"),
+        ("t.x # c\n.y = 1", "t.x # c\n    .y = 1\n"),
+        ("function(x) # c\nx", "function(x) # c\n    x\n"),
+        ("function f() # c\nx", "function f() # c\n    x\n"),
+        ("function{ x } # c\nx", "function{ x } # c\n    x\n"),
+    ];
+    for (source, expected) in cases {
+        let program = parse(source);
+        assert_eq!(pretty::pretty_to_string(program.clone(), 100), expected);
+        assert_roundtrip(&program);
+    }
+}
+
+/// Remove all parentheses from an expression built from operators.
+fn without_parens(expr: Expr) -> Expr {
+    match expr {
+        Expr::Paren { inner, .. } => without_parens(*inner),
+        Expr::Neg {
+            minus,
+            s0,
+            expr,
+            span,
+        } => Expr::Neg {
+            minus,
+            s0,
+            expr: without_parens(*expr).boxed(),
+            span,
+        },
+        Expr::Not {
+            not,
+            s0,
+            expr,
+            span,
+        } => Expr::Not {
+            not,
+            s0,
+            expr: without_parens(*expr).boxed(),
+            span,
+        },
+        Expr::BinOp {
+            left,
+            s0,
+            op,
+            s1,
+            right,
+            span,
+        } => Expr::BinOp {
+            left: without_parens(*left).boxed(),
+            s0,
+            op,
+            s1,
+            right: without_parens(*right).boxed(),
+            span,
+        },
+        expr => expr,
+    }
+}
+
+#[test]
+fn only_required_parentheses() {
+    let sources = [
+        "a - b - c",
+        "a - (b - c)",
+        "(a + b) * c",
+        "a + b * c % d",
+        "a and b and c",
+        "(a and b) and c",
+        "a or b and c == d + e",
+        "(a or b) and c",
+        "-(a + b) * -c",
+        "not (a or b) and not c",
+        "a * --b",
+    ];
+    for source in sources {
+        let program = match parse(source) {
+            Program::Expr { s0, expr, s1, span } => Program::Expr {
+                s0,
+                expr: without_parens(*expr).boxed(),
+                s1,
+                span,
+            },
+            Program::Module { .. } => unreachable!(),
+        };
+        assert_eq!(
+            pretty::pretty_to_string(program, 100),
+            format!("{source}\n")
+        );
+    }
+}
+
+#[test]
+fn random_programs() {
+    let seeds = match env::var("TADA_ROUNDTRIP_SEEDS") {
+        Ok(seeds) => seeds.parse().expect("invalid TADA_ROUNDTRIP_SEEDS"),
+        Err(_) => SEEDS,
+    };
+    for seed in 0..seeds {
+        let mut gen = Gen {
+            rng: Rng::new(seed),
+        };
+        let program = gen.program();
+        eprintln!("seed {seed}"); // Only shown if the test fails
+        assert_roundtrip(&program);
+    }
+}
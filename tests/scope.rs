@@ -0,0 +1,86 @@
+mod common;
+
+#[test]
+fn variables_are_visible_in_inner_scopes() {
+    let source = "'{
+        local a = 1,
+        '{ '{ a } },
+    }";
+    assert_eq!(common::run_ok(source), "1");
+}
+
+#[test]
+fn local_variables_shadow_outer_ones() {
+    let source = "'{
+        local a = 1,
+        local b = '{
+            local a = 2,
+            a,
+        },
+        { a, b },
+    }";
+    assert_eq!(common::run_ok(source), "{ 1, 2 }");
+}
+
+#[test]
+fn assignments_go_to_the_defining_scope() {
+    let source = "'{
+        local a = 1,
+        '{
+            '{ a = 2 },
+            local b = a,
+        },
+        a,
+    }";
+    assert_eq!(common::run_ok(source), "2");
+}
+
+#[test]
+fn local_variables_end_with_their_block() {
+    let source = "'{
+        '{ local a = 1 },
+        a,
+    }";
+    assert_eq!(common::run_ok(source), "nil");
+}
+
+#[test]
+fn undefined_variables_are_assigned_globally() {
+    let source = "'{
+        '{ a = 1 },
+        a,
+    }";
+    assert_eq!(common::run_ok(source), "1");
+}
+
+#[test]
+fn deeply_nested_scopes_see_outer_variables() {
+    // Each level calls the body as a closure over the current scope, nesting
+    // far more scopes than could be written out by hand.
+    let source = "'{
+        local outer = 0,
+        local body = '{ quote: '{
+            local n = 'arg(),
+            outer = outer + 1,
+            local branches = {
+                [true]: function() { body, scope: 'scope() }(n - 1),
+                [false]: function() 'scope(),
+            },
+            branches[n > 0](),
+        } },
+        local innermost = { body, scope: 'scope() }(200),
+        { outer, innermost.outer },
+    }";
+    assert_eq!(common::run_ok(source), "{ 201, 201 }");
+}
+
+#[test]
+fn blocks_evaluate_to_their_last_element() {
+    assert_eq!(common::run_ok("'{ 1, 2 }"), "2");
+    assert_eq!(common::run_ok("'{}"), "nil");
+
+    // `nil` elements aren't stored in the table
+    assert_eq!(common::run_ok("'{ 1, nil }"), "nil");
+    assert_eq!(common::run_ok("'{ x = 5, nil }"), "nil");
+    assert_eq!(common::run_ok("'{ 1, nil, 2 }"), "2");
+}
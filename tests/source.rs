@@ -0,0 +1,68 @@
+use tada::source::{Position, SourceFile};
+use tada::span::Span;
+
+fn file(text: &str) -> SourceFile {
+    SourceFile::new("test.tada".to_owned(), text.to_owned())
+}
+
+#[test]
+fn byte_columns_count_utf8_bytes() {
+    let file = file("\"äöü\" + }");
+    assert_eq!(
+        file.position(8),
+        Position {
+            line: 0,
+            col: 8,
+            byte_col: 11,
+        }
+    );
+    assert_eq!(file.byte_range(Span::new(1, 4)), 1..7);
+}
+
+#[test]
+fn columns_map_back_to_offsets() {
+    let file = file("a = \"äö\",\r\nb\n");
+    assert_eq!(file.offset(0, 6), Some(6));
+    assert_eq!(file.offset(0, 9), Some(9));
+    assert_eq!(file.offset(0, 10), None);
+    assert_eq!(file.offset(1, 0), Some(11));
+    assert_eq!(file.offset(2, 0), Some(13));
+    assert_eq!(file.offset(3, 0), None);
+
+    assert_eq!(file.offset_from_byte_col(0, 7), Some(6));
+    assert_eq!(file.offset_from_byte_col(0, 6), None);
+    assert_eq!(file.offset_from_byte_col(0, 11), Some(9));
+    assert_eq!(file.offset_from_byte_col(0, 12), None);
+    assert_eq!(file.offset_from_byte_col(1, 1), Some(12));
+}
+
+#[test]
+fn byte_ranges_map_back_to_spans() {
+    let file = file("a = \"äö\",\r\nb\n");
+    assert_eq!(file.span_from_bytes(5..9), Some(Span::new(5, 7)));
+    assert_eq!(file.span_from_bytes(13..14), Some(Span::new(11, 12)));
+    assert_eq!(file.span_from_bytes(15..15), Some(Span::new(13, 13)));
+    assert_eq!(file.span_from_bytes(6..9), None);
+    #[allow(clippy::reversed_empty_ranges)]
+    let reversed = 9..5;
+    assert_eq!(file.span_from_bytes(reversed), None);
+    assert_eq!(file.span_from_bytes(0..16), None);
+}
+
+#[test]
+fn mappings_roundtrip() {
+    let file = file("ab\näö\n\nü");
+    for offset in 0..=8 {
+        let position = file.position(offset);
+        assert_eq!(file.offset(position.line, position.col), Some(offset));
+        assert_eq!(
+            file.offset_from_byte_col(position.line, position.byte_col),
+            Some(offset)
+        );
+        let byte = file.byte_offset(offset);
+        assert_eq!(
+            file.span_from_bytes(byte..byte),
+            Some(Span::new(offset, offset))
+        );
+    }
+}
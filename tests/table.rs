@@ -0,0 +1,45 @@
+use tada::eval::Runtime;
+use tada::table::Key;
+use tada::value::Value;
+
+mod common;
+
+#[test]
+fn named_keys_keep_insertion_order() {
+    let source = "'{
+        t = { zeta: 1, alpha: 2, 10, 20 },
+        t.mid = 3,
+        t[5] = 4,
+        t.alpha = nil,
+        t.alpha = 5,
+        t,
+    }";
+    // Positional elements come first. Removing a key and inserting it again
+    // moves it to the end.
+    assert_eq!(
+        common::run_ok(source),
+        "{ 10, 20, zeta: 1, mid: 3, [5]: 4, alpha: 5 }"
+    );
+}
+
+#[test]
+fn removing_a_positional_element_keeps_the_rest() {
+    let source = "'{
+        t = { 0, 1, 2 },
+        t[1] = nil,
+        t,
+    }";
+    assert_eq!(common::run_ok(source), "{ 0, [2]: 2 }");
+}
+
+#[test]
+fn hosts_can_build_tables() {
+    let mut runtime = Runtime::new();
+    let table = runtime.new_table();
+    table.insert(Key::from("b"), Value::Int(1));
+    table.insert(Key::from(0), Value::Int(2));
+    table.insert(Key::from("a"), Value::Bool(true));
+    let keys = table.keys().collect::<Vec<_>>();
+    assert_eq!(keys, [Key::from(0), Key::from("b"), Key::from("a")]);
+    assert_eq!(Value::Table(table).to_string(), "{ 2, b: 1, a: true }");
+}